@@ -12,9 +12,11 @@ export default function Kitties (props) {
 
   const [kitties, setKitties] = useState([])
   const [status, setStatus] = useState('')
+  const [ids, setIds] = useState([])
   const [dnas, setDnas] = useState([])
   const [owners, setOwners] = useState([])
-  const formatDna = (dna) => dna.isNone ? {} : dna.value.toU8a();
+  const formatDna = (kitty) => kitty.isNone ? {} : kitty.value.dna.toU8a();
+  const formatOwner = (kitty) => kitty.isNone ? null : kitty.value.owner.toHuman();
 
   const fetchKitties = () => {
     // TODO: 在这里调用 `api.query.kittiesModule.*` 函数去取得猫咪的信息。
@@ -22,29 +24,28 @@ export default function Kitties (props) {
     //   - 共有多少只猫咪
     //   - 每只猫咪的主人是谁
     //   - 每只猫咪的 DNA 是什么，用来组合出它的形态
-    let unsubCount = null;
-    let unsubKitty = null;
-    let unsubOwner = null;
+    let unsubHeads = null;
 
     const asyncFetch = async () => {
-      unsubCount = await api.query.kittiesModule.kittiesCount(async (res) => {
-        const total = res.isNone ?0: res.value.toNumber();
-        const indexes = [...Array(total).keys()];
-
-        unsubKitty = await api.query.kittiesModule.kitties.multi(indexes, kitties => {
-          setDnas(kitties.map((dna, id) => formatDna(dna)));
+      // 每个收藏集有自己的计数，猫咪的编号是 (收藏集, 序号)，每个新区块重新读取
+      unsubHeads = await api.rpc.chain.subscribeNewHeads(async () => {
+        const counts = await api.query.kittiesModule.kittiesCount.entries();
+        const ids = counts.flatMap(([key, count]) => {
+          const collectionId = key.args[0].toNumber();
+          return [...Array(count.toNumber()).keys()].map(index => [collectionId, index]);
         });
 
-        unsubOwner = await api.query.kittiesModule.owner.multi(indexes, owners => {
-          setOwners(owners.map(owner => owner.toHuman()));
-        });
+        const kitties = await api.query.kittiesModule.kitties.multi(ids);
+        setIds(ids);
+        setDnas(kitties.map(formatDna));
+        setOwners(kitties.map(formatOwner));
       });
     }
 
     asyncFetch();
 
     return () => {
-      unsubCount && unsubCount() && unsubKitty && unsubKitty() && unsubOwner && unsubOwner()
+      unsubHeads && unsubHeads()
     }
   }
 
@@ -58,11 +59,14 @@ export default function Kitties (props) {
     //  }, { id: ..., dna: ..., owner: ... }]
     //  ```
     // 这个 kitties 会传入 <KittyCards/> 然后对每只猫咪进行处理
-    setKitties(dnas.map((dna, id) => ({ id, dna, owner: owners[id] })))
+    // 已销毁的猫咪没有主人，不再显示
+    setKitties(dnas
+      .map((dna, i) => ({ id: ids[i], dna, owner: owners[i] }))
+      .filter(kitty => kitty.owner !== null))
   }
 
   useEffect(fetchKitties, [api, keyring])
-  useEffect(populateKitties, [dnas, owners])
+  useEffect(populateKitties, [ids, dnas, owners])
 
   return <Grid.Column width={16}>
     <h1>小毛孩</h1>
@@ -1,7 +1,8 @@
 use node_template_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-	SystemConfig, WASM_BINARY,
+	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, OcwDemoConfig, Signature,
+	SudoConfig, SystemConfig, WASM_BINARY,
 };
+use node_template_runtime::pallet_ocw::PriceSource;
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{sr25519, Pair, Public};
@@ -59,6 +60,12 @@ pub fn development_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
 				],
+				// Price reporters, one per node
+				vec![
+					get_account_id_from_seed::<sr25519::Public>("Alice"),
+					get_account_id_from_seed::<sr25519::Public>("Bob"),
+					get_account_id_from_seed::<sr25519::Public>("Charlie"),
+				],
 				true,
 			)
 		},
@@ -106,6 +113,12 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 				],
+				// Price reporters, one per node
+				vec![
+					get_account_id_from_seed::<sr25519::Public>("Alice"),
+					get_account_id_from_seed::<sr25519::Public>("Bob"),
+					get_account_id_from_seed::<sr25519::Public>("Charlie"),
+				],
 				true,
 			)
 		},
@@ -128,6 +141,7 @@ fn testnet_genesis(
 	initial_authorities: Vec<(AuraId, GrandpaId)>,
 	root_key: AccountId,
 	endowed_accounts: Vec<AccountId>,
+	reporters: Vec<AccountId>,
 	_enable_println: bool,
 ) -> GenesisConfig {
 	GenesisConfig {
@@ -148,7 +162,22 @@ fn testnet_genesis(
 		},
 		sudo: SudoConfig {
 			// Assign network admin rights.
-			key: root_key,
+			key: root_key.clone(),
+		},
+		ocw_demo: OcwDemoConfig {
+			// Served by `scripts/mock-price-server.py`, so development chains need no internet access.
+			price_sources: [(&b"DOT"[..], "polkadot"), (b"KSM", "kusama"), (b"USDT", "tether")]
+				.iter()
+				.map(|(asset, id)| PriceSource {
+					asset: asset.to_vec(),
+					url: format!("http://localhost:8000/v2/assets/{}", id).into_bytes(),
+					headers: vec![],
+					json_path: b"data.priceUsd".to_vec(),
+				})
+				.collect(),
+			// Each reporter runs its own node with its key as the node's only `demo` offchain
+			// worker key; a price needs `MinReporters` of them.
+			reporters,
 		},
 	}
 }
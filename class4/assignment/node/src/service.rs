@@ -70,16 +70,18 @@ pub fn new_partial(
 	let client = Arc::new(client);
 
 	let keystore = keystore_container.sync_keystore();
-	if config.offchain_worker.enabled {
+	if let (true, Some(seed)) = (config.offchain_worker.enabled, config.dev_key_seed.as_deref()) {
 		// Initialize seed for signing transaction using off-chain workers. This is a convenience
-		// so learners can see the transactions submitted simply running the node.
+		// so learners can see the transactions submitted simply running the node: a node started
+		// with `--alice`, `--bob` or `--charlie` reports prices as that account, and each node
+		// holds a single worker key so that every reporter is a separate node.
 		// Typically these keys should be inserted with RPC calls to `author_insertKey`.
 		sp_keystore::SyncCryptoStore::sr25519_generate_new(
 			&*keystore,
 			runtime::pallet_ocw::KEY_TYPE,
-			Some("//Alice"),
+			Some(seed),
 		)
-		.expect("Creating key with account Alice should succeed.");
+		.expect("Creating the offchain worker key from the dev seed should succeed.");
 	}
 
 	let telemetry = telemetry.map(|(worker, telemetry)| {
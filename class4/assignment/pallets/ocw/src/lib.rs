@@ -1,18 +1,22 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 pub use pallet::*;
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
 
 #[frame_support::pallet]
 pub mod pallet {
 	//! A demonstration of an offchain worker that sends onchain callbacks
-	use core::{convert::TryInto, fmt};
+	use core::convert::TryInto;
 	use parity_scale_codec::{Decode, Encode};
 	use frame_support::pallet_prelude::*;
 	use frame_system::{
 		pallet_prelude::*,
 		offchain::{
 			AppCrypto, CreateSignedTransaction, SendSignedTransaction, SendUnsignedTransaction,
-			SignedPayload, Signer, SigningTypes, SubmitTransaction,
+			SignedPayload, Signer, SigningTypes,
 		},
 	};
 	use sp_core::{crypto::KeyTypeId};
@@ -20,7 +24,7 @@ pub mod pallet {
 	use sp_runtime::{
 		offchain as rt_offchain,
 		traits::{
-			BlockNumberProvider
+			BlockNumberProvider, IdentifyAccount
 		},
 		offchain::{
 			storage::StorageValueRef,
@@ -31,9 +35,11 @@ pub mod pallet {
 		},
 		RuntimeDebug,
 	};
-	use sp_std::{collections::vec_deque::VecDeque, prelude::*, str};
+	use sp_std::{collections::vec_deque::VecDeque, prelude::*, str, string::ToString};
 
 	use serde::{Deserialize, Deserializer};
+	#[cfg(feature = "std")]
+	use serde::Serialize;
 
 	/// Defines application identifier for crypto keys of this module.
 	///
@@ -47,11 +53,6 @@ pub mod pallet {
 	/// The type to sign and send transactions.
 	const UNSIGNED_TXS_PRIORITY: u64 = 100;
 
-	// We are fetching information from the github public API about organization`substrate-developer-hub`.
-	// const HTTP_REMOTE_REQUEST: &str = "https://api.github.com/orgs/substrate-developer-hub";
-	const HTTP_REMOTE_REQUEST: &str = "https://api.coincap.io/v2/assets/polkadot";
-	const HTTP_HEADER_USER_AGENT: &str = "yangluo1024";
-
 	const FETCH_TIMEOUT_PERIOD: u64 = 3000; // in milli-seconds
 	const LOCK_TIMEOUT_EXPIRATION: u64 = FETCH_TIMEOUT_PERIOD + 1000; // in milli-seconds
 	const LOCK_BLOCK_EXPIRATION: u32 = 3; // in block number
@@ -87,8 +88,8 @@ pub mod pallet {
 
 	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
 	pub struct Payload<Public> {
-		number: u64,
-		public: Public,
+		pub number: u64,
+		pub public: Public,
 	}
 
 	impl<T: SigningTypes> SignedPayload<T> for Payload<T::Public> {
@@ -97,23 +98,40 @@ pub mod pallet {
 		}
 	}
 
-	// ref: https://serde.rs/container-attrs.html#crate
-	#[derive(Deserialize, Encode, Decode, Default)]
-	struct GithubInfo {
-		// Specify our own deserializing function to convert JSON string to vector of bytes
-		#[serde(deserialize_with = "de_string_to_bytes")]
-		login: Vec<u8>,
-		#[serde(deserialize_with = "de_string_to_bytes")]
-		blog: Vec<u8>,
-		public_repos: u32,
+	/// The symbol prices are kept under, e.g. `DOT` or `USDT`.
+	pub type AssetSymbol = Vec<u8>;
+
+	/// An HTTP endpoint the offchain worker reads the price of an asset from.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+	#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+	pub struct PriceSource {
+		/// The asset this source prices.
+		pub asset: AssetSymbol,
+		/// The URL to `GET`.
+		pub url: Vec<u8>,
+		/// Request headers as `(name, value)`, e.g. the `User-Agent` some APIs require.
+		pub headers: Vec<(Vec<u8>, Vec<u8>)>,
+		/// Where the price is in the JSON response: object keys and array indices separated
+		/// by dots, e.g. `data.priceUsd`. The price may be a JSON string or number.
+		pub json_path: Vec<u8>,
 	}
 
-	#[derive(Debug, Deserialize, Encode, Decode, Default)]
-    #[allow(non_snake_case)]
-	struct PriceInfo {
-		// Specify our own deserializing function to convert JSON string to vector of bytes
-		#[serde(deserialize_with = "de_string_to_bytes")]
-		priceUsd: Vec<u8>,
+	impl PriceSource {
+		/// Whether the URL, headers and path are all valid UTF-8, as the HTTP API needs.
+		fn is_valid(&self) -> bool {
+			str::from_utf8(&self.url).is_ok()
+				&& str::from_utf8(&self.json_path).is_ok()
+				&& self.headers.iter().all(|(name, value)| str::from_utf8(name).is_ok() && str::from_utf8(value).is_ok())
+		}
+	}
+
+	/// The prices submitted for an asset since its last price was published.
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+	pub struct Round<AccountId, BlockNumber> {
+		/// The block of the first submission.
+		pub started: BlockNumber,
+		/// The latest submission of each reporter, in the order they first submitted.
+		pub submissions: Vec<(AccountId, (u64, Permill))>,
 	}
 
 	#[derive(Debug, Deserialize, Encode, Decode, Default)]
@@ -127,20 +145,6 @@ pub mod pallet {
 		Ok(s.as_bytes().to_vec())
 	}
 
-	impl fmt::Debug for GithubInfo {
-		// `fmt` converts the vector of bytes inside the struct back to string for
-		//   more friendly display.
-		fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-			write!(
-				f,
-				"{{ login: {}, blog: {}, public_repos: {} }}",
-				str::from_utf8(&self.login).map_err(|_| fmt::Error)?,
-				str::from_utf8(&self.blog).map_err(|_| fmt::Error)?,
-				&self.public_repos
-				)
-		}
-	}
-
 	#[pallet::config]
 	pub trait Config: frame_system::Config + CreateSignedTransaction<Call<Self>> {
 		/// The overarching event type.
@@ -149,6 +153,24 @@ pub mod pallet {
 		type Call: From<Call<Self>>;
 		/// The identifier type for an offchain worker.
 		type AuthorityId: AppCrypto<Self::Public, Self::Signature>;
+		/// The maximum number of price sources.
+		#[pallet::constant]
+		type MaxPriceSources: Get<u32>;
+		/// How many blocks after its first submission a round closes.
+		#[pallet::constant]
+		type RoundLength: Get<Self::BlockNumber>;
+		/// How many reporters close a round as soon as they have all submitted.
+		#[pallet::constant]
+		type Quorum: Get<u32>;
+		/// The fewest submissions a closed round needs for its median to be published; rounds
+		/// with fewer are discarded. At least 3, so no single reporter sets the price.
+		#[pallet::constant]
+		type MinReporters: Get<u32>;
+		/// The origin that adds and removes reporters.
+		type ReporterOrigin: EnsureOrigin<Self::Origin>;
+		/// The maximum number of reporters.
+		#[pallet::constant]
+		type MaxReporters: Get<u32>;
 	}
 
 	#[pallet::pallet]
@@ -163,15 +185,71 @@ pub mod pallet {
 	// https://substrate.dev/docs/en/knowledgebase/runtime/storage#declaring-storage-items
 	pub type Numbers<T> = StorageValue<_, VecDeque<u64>, ValueQuery>;
 
+	/// The latest prices of each asset, oldest first.
 	#[pallet::storage]
 	#[pallet::getter(fn prices)]
-	pub type Prices<T> = StorageValue<_, VecDeque<(u64, Permill)>, ValueQuery>;
+	pub type Prices<T> = StorageMap<_, Blake2_128Concat, AssetSymbol, VecDeque<(u64, Permill)>, ValueQuery>;
+
+	/// The open round of each asset.
+	#[pallet::storage]
+	#[pallet::getter(fn rounds)]
+	pub type Rounds<T: Config> =
+		StorageMap<_, Blake2_128Concat, AssetSymbol, Round<T::AccountId, T::BlockNumber>, OptionQuery>;
+
+	/// The accounts allowed to submit numbers and prices, sorted.
+	#[pallet::storage]
+	#[pallet::getter(fn reporters)]
+	pub type Reporters<T: Config> = StorageValue<_, Vec<T::AccountId>, ValueQuery>;
+
+	/// The endpoints the offchain worker tries, in order, until one returns the price of their asset.
+	#[pallet::storage]
+	#[pallet::getter(fn price_sources)]
+	pub type PriceSources<T> = StorageValue<_, Vec<PriceSource>, ValueQuery>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		pub price_sources: Vec<PriceSource>,
+		pub reporters: Vec<T::AccountId>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { price_sources: Vec::new(), reporters: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			assert!(self.price_sources.len() <= T::MaxPriceSources::get() as usize, "too many price sources");
+			assert!(self.price_sources.iter().all(PriceSource::is_valid), "invalid price source");
+			PriceSources::<T>::put(&self.price_sources);
+
+			let mut reporters = self.reporters.clone();
+			reporters.sort();
+			reporters.dedup();
+			assert!(reporters.len() <= T::MaxReporters::get() as usize, "too many reporters");
+			Reporters::<T>::put(reporters);
+		}
+	}
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
 		NewNumber(Option<T::AccountId>, u64),
-		NewPrice(Option<T::AccountId>, (u64, Permill)),
+		/// A price was submitted to the open round of an asset. [who, asset, price]
+		NewPrice(Option<T::AccountId>, AssetSymbol, (u64, Permill)),
+		/// A round closed and the median of its submissions was published. [asset, price, reporters]
+		PricePublished(AssetSymbol, (u64, Permill), Vec<T::AccountId>),
+		/// A round closed with too few submissions and no price was published. [asset, submissions]
+		RoundDiscarded(AssetSymbol, u32),
+		/// The price sources were replaced. [number of sources]
+		PriceSourcesSet(u32),
+		/// An account may now submit numbers and prices. [who]
+		ReporterAdded(T::AccountId),
+		/// An account may no longer submit numbers and prices. [who]
+		ReporterRemoved(T::AccountId),
 	}
 
 	// Errors inform users that something went wrong.
@@ -184,18 +262,58 @@ pub mod pallet {
 		NoLocalAcctForSigning,
 		OffchainSignedTxError,
 
-		// Error returned when making unsigned transactions in off-chain worker
-		OffchainUnsignedTxError,
-
 		// Error returned when making unsigned transactions with signed payloads in off-chain worker
 		OffchainUnsignedTxSignedPayloadError,
 
-		// Error returned when fetching github info
+		// Error returned when fetching a price source
 		HttpFetchingError,
+
+		// Error returned when a price source's response has no price at its JSON path
+		PriceParsingError,
+
+		// Error returned when no price source returned a price
+		NoPriceAvailable,
+
+		// Errors returned when setting the price sources
+		TooManyPriceSources,
+		InvalidPriceSource,
+
+		// Error returned when submitting the price of an asset without price sources
+		UnknownAsset,
+
+		// Error returned when an account that is not a reporter submits, or is removed
+		NotReporter,
+
+		// Errors returned when adding a reporter
+		AlreadyReporter,
+		TooManyReporters,
 	}
 
 	#[pallet::hooks]
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn integrity_test() {
+			// With two submissions the median is their mean, which either reporter can move at will.
+			assert!(T::MinReporters::get() >= 3, "a price needs at least 3 reporters");
+			assert!(T::Quorum::get() >= T::MinReporters::get(), "the quorum must meet the minimum reporters");
+			assert!(T::MaxReporters::get() >= T::Quorum::get(), "the quorum must be reachable by the reporters");
+		}
+
+		/// Close the rounds that have run for `RoundLength` blocks.
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			// There is at most one round per asset with a price source, so this stays small.
+			let rounds: Vec<_> = Rounds::<T>::iter().collect();
+			let reads = rounds.len() as Weight;
+			let mut writes: Weight = 0;
+			for (asset, round) in rounds {
+				if now >= round.started + T::RoundLength::get() {
+					Rounds::<T>::remove(&asset);
+					Self::publish_price(asset, round);
+					writes += 3;
+				}
+			}
+			T::DbWeight::get().reads_writes(reads, writes)
+		}
+
 		/// Offchain Worker entry point.
 		///
 		/// By implementing `fn offchain_worker` you declare a new offchain worker.
@@ -210,17 +328,15 @@ pub mod pallet {
 
 			// Here we are showcasing various techniques used when running off-chain workers (ocw)
 			// 1. Sending signed transaction from ocw
-			// 2. Sending unsigned transaction from ocw
-			// 3. Sending unsigned transactions with signed payloads from ocw
-			// 4. Fetching JSON via http requests in ocw
-			const TX_TYPES: u32 = 5;
+			// 2. Sending unsigned transactions with signed payloads from ocw
+			// 3. Fetching JSON via http requests in ocw
+			// Bare unsigned transactions are not sent: anyone could send the same call.
+			const TX_TYPES: u32 = 3;
 			let modu = block_number.try_into().map_or(TX_TYPES, |bn: usize| (bn as u32) % TX_TYPES);
 			let result = match modu {
 				0 => Self::offchain_signed_tx(block_number),
-				1 => Self::offchain_unsigned_tx(block_number),
-				2 => Self::offchain_unsigned_tx_signed_payload(block_number),
-				3 => Self::fetch_github_info(),
-				4 => Self::fetch_price_info(),
+				1 => Self::offchain_unsigned_tx_signed_payload(block_number),
+				2 => Self::fetch_price_info(block_number / TX_TYPES.into()),
 				_ => Err(Error::<T>::UnknownOffchainMux),
 			};
 
@@ -250,11 +366,13 @@ pub mod pallet {
 			.build();
 
 			match call {
-				Call::submit_number_unsigned(_number) => valid_tx(b"submit_number_unsigned".to_vec()),
 				Call::submit_number_unsigned_with_signed_payload(ref payload, ref signature) => {
 					if !SignedPayload::<T>::verify::<T::AuthorityId>(payload, signature.clone()) {
 						return InvalidTransaction::BadProof.into();
 					}
+					if !Self::is_reporter(&payload.public.clone().into_account()) {
+						return InvalidTransaction::BadSigner.into();
+					}
 					valid_tx(b"submit_number_unsigned_with_signed_payload".to_vec())
 				},
 				_ => InvalidTransaction::Call.into(),
@@ -267,6 +385,7 @@ pub mod pallet {
 		#[pallet::weight(10000)]
 		pub fn submit_number_signed(origin: OriginFor<T>, number: u64) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			ensure!(Self::is_reporter(&who), Error::<T>::NotReporter);
 			log::info!("submit_number_signed: ({}, {:?})", number, who);
 			Self::append_or_replace_number(number);
 
@@ -275,22 +394,76 @@ pub mod pallet {
 		}
 
 		#[pallet::weight(10000)]
-		pub fn submit_price_signed(origin: OriginFor<T>, price: (u64, Permill)) -> DispatchResult {
+		pub fn submit_price_signed(origin: OriginFor<T>, asset: AssetSymbol, price: (u64, Permill)) -> DispatchResult {
 			let who = ensure_signed(origin)?;
+			ensure!(Self::is_reporter(&who), Error::<T>::NotReporter);
+			// Only assets with a price source are kept, so the map stays bounded.
+			ensure!(Self::price_sources().iter().any(|source| source.asset == asset), Error::<T>::UnknownAsset);
 			// TODO: Permill std::fmt::Display
 			// log::info!("submit_price_signed: ({}.{}, {:?})", price.0, price.1, who);
-			Self::append_or_replace_price(price);
-			Self::deposit_event(Event::NewPrice(Some(who), price));
+			Self::deposit_event(Event::NewPrice(Some(who.clone()), asset.clone(), price));
+
+			// 每轮每个报价人只保留最新报价，达到法定人数即提前结束本轮
+			let mut round = Self::rounds(&asset).unwrap_or_else(|| Round {
+				started: <frame_system::Pallet<T>>::block_number(),
+				submissions: Vec::new(),
+			});
+			match round.submissions.iter_mut().find(|(reporter, _)| *reporter == who) {
+				Some(submission) => submission.1 = price,
+				None => round.submissions.push((who, price)),
+			}
+			if round.submissions.len() >= T::Quorum::get() as usize {
+				Rounds::<T>::remove(&asset);
+				Self::publish_price(asset, round);
+			} else {
+				Rounds::<T>::insert(&asset, round);
+			}
 			Ok(())
 		}
 
+		//添加报价人
 		#[pallet::weight(10000)]
-		pub fn submit_number_unsigned(origin: OriginFor<T>, number: u64) -> DispatchResult {
-			let _ = ensure_none(origin)?;
-			log::info!("submit_number_unsigned: {}", number);
-			Self::append_or_replace_number(number);
+		pub fn add_reporter(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			T::ReporterOrigin::ensure_origin(origin)?;
+
+			Reporters::<T>::try_mutate(|reporters| {
+				let index = reporters.binary_search(&who).err().ok_or(Error::<T>::AlreadyReporter)?;
+				ensure!(reporters.len() < T::MaxReporters::get() as usize, Error::<T>::TooManyReporters);
+				reporters.insert(index, who.clone());
+				Ok::<_, Error<T>>(())
+			})?;
+			Self::deposit_event(Event::ReporterAdded(who));
+			Ok(())
+		}
 
-			Self::deposit_event(Event::NewNumber(None, number));
+		//移除报价人，并丢弃其在未结束轮次中的报价
+		#[pallet::weight(10000)]
+		pub fn remove_reporter(origin: OriginFor<T>, who: T::AccountId) -> DispatchResult {
+			T::ReporterOrigin::ensure_origin(origin)?;
+
+			Reporters::<T>::try_mutate(|reporters| {
+				let index = reporters.binary_search(&who).map_err(|_| Error::<T>::NotReporter)?;
+				reporters.remove(index);
+				Ok::<_, Error<T>>(())
+			})?;
+			Rounds::<T>::translate(|_, mut round: Round<T::AccountId, T::BlockNumber>| {
+				round.submissions.retain(|(reporter, _)| *reporter != who);
+				Some(round).filter(|round| !round.submissions.is_empty())
+			});
+			Self::deposit_event(Event::ReporterRemoved(who));
+			Ok(())
+		}
+
+		//设置价格来源，由root管理
+		#[pallet::weight(10000)]
+		pub fn set_price_sources(origin: OriginFor<T>, sources: Vec<PriceSource>) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(sources.len() <= T::MaxPriceSources::get() as usize, Error::<T>::TooManyPriceSources);
+			ensure!(sources.iter().all(PriceSource::is_valid), Error::<T>::InvalidPriceSource);
+
+			let count = sources.len() as u32;
+			PriceSources::<T>::put(sources);
+			Self::deposit_event(Event::PriceSourcesSet(count));
 			Ok(())
 		}
 
@@ -301,6 +474,8 @@ pub mod pallet {
 			let _ = ensure_none(origin)?;
 			// we don't need to verify the signature here because it has been verified in
 			//   `validate_unsigned` function when sending out the unsigned tx.
+			// The signer may have stopped being a reporter since then, so check that again.
+			ensure!(Self::is_reporter(&payload.public.clone().into_account()), Error::<T>::NotReporter);
 			let Payload { number, public } = payload;
 			log::info!("submit_number_unsigned_with_signed_payload: ({}, {:?})", number, public);
 			Self::append_or_replace_number(number);
@@ -323,8 +498,48 @@ pub mod pallet {
 			});
 		}
 
-		fn append_or_replace_price(price: (u64, Permill)) {
-			Prices::<T>::mutate(|prices| {
+		/// Whether `who` may submit numbers and prices.
+		pub fn is_reporter(who: &T::AccountId) -> bool {
+			Self::reporters().binary_search(who).is_ok()
+		}
+
+		/// Publish the median of the submissions of a closed round, or discard the round if
+		///   fewer than `MinReporters` submitted.
+		fn publish_price(asset: AssetSymbol, round: Round<T::AccountId, T::BlockNumber>) {
+			let submissions = round.submissions.len() as u32;
+			if submissions < T::MinReporters::get() {
+				Self::deposit_event(Event::RoundDiscarded(asset, submissions));
+				return;
+			}
+			let mut prices: Vec<_> = round.submissions.iter().map(|(_, price)| *price).collect();
+			let price = match Self::median(&mut prices) {
+				Some(price) => price,
+				None => return,
+			};
+			Self::append_or_replace_price(&asset, price);
+			let reporters = round.submissions.into_iter().map(|(reporter, _)| reporter).collect();
+			Self::deposit_event(Event::PricePublished(asset, price, reporters));
+		}
+
+		/// The median of `prices`; the mean of the middle two for an even number of prices.
+		pub(crate) fn median(prices: &mut [(u64, Permill)]) -> Option<(u64, Permill)> {
+			if prices.is_empty() {
+				return None;
+			}
+			prices.sort();
+			let middle = prices.len() / 2;
+			if prices.len() % 2 == 1 {
+				return Some(prices[middle]);
+			}
+
+			// Average in millionths, so the fractions carry into the integer part.
+			let millionths = |(int, fraction): (u64, Permill)| int as u128 * 1_000_000 + fraction.deconstruct() as u128;
+			let mean = (millionths(prices[middle - 1]) + millionths(prices[middle])) / 2;
+			Some(((mean / 1_000_000) as u64, Permill::from_parts((mean % 1_000_000) as u32)))
+		}
+
+		fn append_or_replace_price(asset: &AssetSymbol, price: (u64, Permill)) {
+			Prices::<T>::mutate(asset, |prices| {
 				if prices.len() == NUM_VEC_LEN {
 					let _ = prices.pop_front();
 				}
@@ -333,40 +548,56 @@ pub mod pallet {
 			});
 		}
 
-		fn fetch_price_info() -> Result<(), Error<T>> {
-			let s_info = StorageValueRef::persistent(b"offchain-demo::price-info");
-
-			if let Ok(Some(price_info)) = s_info.get::<(u64, Permill)>() {
-				// price-info has already been fetched. Return early.
-				log::info!("cached price-info: {:?}", price_info);
-				return Self::offchain_signed_tx_update_prices(price_info);
-			}
-
+		/// Fetch the price of one asset from the first of its `PriceSources` that returns one,
+		///   and submit it. Each run takes the next asset in turn, as the signed transactions
+		///   of one run would share a nonce.
+		pub(crate) fn fetch_price_info(turn: T::BlockNumber) -> Result<(), Error<T>> {
+			// Since off-chain storage can be accessed by off-chain workers from multiple runs, it is important to lock
+			//   it before doing heavy computations or write operations.
+			//
+			// There are four ways of defining a lock:
+			//   1) `new` - lock with default time and block exipration
+			//   2) `with_deadline` - lock with default block but custom time expiration
+			//   3) `with_block_deadline` - lock with default time but custom block expiration
+			//   4) `with_block_and_time_deadline` - lock with custom time and block expiration
+			// Here we choose the most custom one for demonstration purpose.
 			let mut lock = StorageLock::<BlockAndTime<Self>>::with_block_and_time_deadline(
 				b"offchain-demo::lock", LOCK_BLOCK_EXPIRATION,
 				rt_offchain::Duration::from_millis(LOCK_TIMEOUT_EXPIRATION)
 				);
 
-			// We try to acquire the lock here. If failed, we know the `fetch_n_parse` part inside is being
+			// We try to acquire the lock here. If failed, we know the fetching part inside is being
 			//   executed by previous run of ocw, so the function just returns.
 			if let Ok(_guard) = lock.try_lock() {
-				match Self::fetch_one_parse() {
-					Ok(price_info) => {
-						s_info.set(&price_info);
-						return Self::offchain_signed_tx_update_prices(price_info);
+				let sources = Self::price_sources();
+				let mut assets: Vec<&AssetSymbol> = Vec::new();
+				for source in &sources {
+					if !assets.contains(&&source.asset) {
+						assets.push(&source.asset);
 					}
-					Err(err) => { return Err(err); }
 				}
+				let turn: usize = turn.try_into().unwrap_or_default();
+				let asset = match assets.get(turn.checked_rem(assets.len()).unwrap_or_default()) {
+					Some(asset) => (*asset).clone(),
+					None => return Err(<Error<T>>::NoPriceAvailable),
+				};
+
+				let price_info = sources.iter().filter(|source| source.asset == asset).find_map(|source| {
+					Self::fetch_price(source)
+						.map_err(|e| log::warn!("price source {:?} failed: {:?}", source, e))
+						.ok()
+				}).ok_or(<Error<T>>::NoPriceAvailable)?;
+				return Self::offchain_signed_tx_update_prices(asset, price_info);
 			}
 			Ok(())
 		}
 
-		fn offchain_signed_tx_update_prices(price_info: (u64, Permill)) -> Result<(), Error<T>> {
+		fn offchain_signed_tx_update_prices(asset: AssetSymbol, price_info: (u64, Permill)) -> Result<(), Error<T>> {
 			// 使用签名交易提交到链上，数据更为安全, substrate默认会拒绝不具签名的交易，因此如果使用不具签名的交易，还得添加额外的验证相关代码
 			let signer = Signer::<T, T::AuthorityId>::any_account();
 			let result = signer.send_signed_transaction(|_acct|
 				// This is the on-chain function
-				Call::submit_price_signed(price_info)
+				Call::submit_price_signed(asset.clone(), price_info)
 				);
 
 			// Display error if the signed tx fails.
@@ -385,57 +616,9 @@ pub mod pallet {
 		}
 
 
-		/// Check if we have fetched github info before. If yes, we can use the cached version
-		///   stored in off-chain worker storage `storage`. If not, we fetch the remote info and
-		///   write the info into the storage for future retrieval.
-		fn fetch_github_info() -> Result<(), Error<T>> {
-			// Create a reference to Local Storage value.
-			// Since the local storage is common for all offchain workers, it's a good practice
-			// to prepend our entry with the pallet name.
-			let s_info = StorageValueRef::persistent(b"offchain-demo::gh-info");
-
-			// Local storage is persisted and shared between runs of the offchain workers,
-			// offchain workers may run concurrently. We can use the `mutate` function to
-			// write a storage entry in an atomic fashion.
-			//
-			// With a similar API as `StorageValue` with the variables `get`, `set`, `mutate`.
-			// We will likely want to use `mutate` to access
-			// the storage comprehensively.
-			//
-			if let Ok(Some(gh_info)) = s_info.get::<GithubInfo>() {
-				// gh-info has already been fetched. Return early.
-				log::info!("cached gh-info: {:?}", gh_info);
-				return Ok(());
-			}
-
-			// Since off-chain storage can be accessed by off-chain workers from multiple runs, it is important to lock
-			//   it before doing heavy computations or write operations.
-			//
-			// There are four ways of defining a lock:
-			//   1) `new` - lock with default time and block exipration
-			//   2) `with_deadline` - lock with default block but custom time expiration
-			//   3) `with_block_deadline` - lock with default time but custom block expiration
-			//   4) `with_block_and_time_deadline` - lock with custom time and block expiration
-			// Here we choose the most custom one for demonstration purpose.
-			let mut lock = StorageLock::<BlockAndTime<Self>>::with_block_and_time_deadline(
-				b"offchain-demo::lock", LOCK_BLOCK_EXPIRATION,
-				rt_offchain::Duration::from_millis(LOCK_TIMEOUT_EXPIRATION)
-				);
-
-			// We try to acquire the lock here. If failed, we know the `fetch_n_parse` part inside is being
-			//   executed by previous run of ocw, so the function just returns.
-			if let Ok(_guard) = lock.try_lock() {
-				match Self::fetch_n_parse() {
-					Ok(gh_info) => { s_info.set(&gh_info); }
-					Err(err) => { return Err(err); }
-				}
-			}
-			Ok(())
-		}
-
-		/// Fetch from remote and deserialize the JSON to a struct
-		fn fetch_one_parse() -> Result<(u64, Permill), Error<T>> {
-			let resp_bytes = Self::fetch_from_remote().map_err(|e| {
+		/// Fetch `source` and read the price at its JSON path.
+		pub(crate) fn fetch_price(source: &PriceSource) -> Result<(u64, Permill), Error<T>> {
+			let resp_bytes = Self::fetch_from_remote(source).map_err(|e| {
 				log::error!("fetch_from_remote error: {:?}", e);
 				<Error<T>>::HttpFetchingError
 			})?;
@@ -444,56 +627,65 @@ pub mod pallet {
 			// Print out our fetched JSON string
 			log::info!("{}", resp_str);
 
-			// Deserializing JSON to struct, thanks to `serde` and `serde_derive`
-			let mut price_info: PriceInfo =
-			serde_json::from_str(&resp_str).map_err(|_| <Error<T>>::HttpFetchingError)?;
+			let json: serde_json::Value =
+			serde_json::from_str(&resp_str).map_err(|_| <Error<T>>::PriceParsingError)?;
+			let price = match Self::json_at(&json, &source.json_path) {
+				Some(serde_json::Value::String(price)) => price.clone(),
+				Some(serde_json::Value::Number(price)) => price.to_string(),
+				_ => return Err(<Error<T>>::PriceParsingError),
+			};
 
-			let price_tuple = Self::str_to_tuple(&mut price_info.priceUsd);
-			price_tuple
+			Self::str_to_tuple(&price).ok_or(<Error<T>>::PriceParsingError)
 		}
 
-		fn str_to_tuple(s: &mut Vec<u8>) -> Result<(u64, Permill), Error<T>> {
-			let price_str = str::from_utf8(&s).unwrap();
-			let prices = price_str.split(".").collect::<Vec<&str>>();
-			let price_int = prices[0].parse::<u64>().unwrap();
-			let price_tmp= prices[1].as_bytes().to_vec()[..7].to_vec();
-			let price_decimal = str::from_utf8(&price_tmp).unwrap().parse::<u32>().unwrap();
-			Ok((price_int, Permill::from_parts(price_decimal)))
+		/// The value at `path` in `json`, where `path` is object keys and array indices
+		///   separated by dots, e.g. `data.0.price`.
+		pub(crate) fn json_at<'a>(json: &'a serde_json::Value, path: &[u8]) -> Option<&'a serde_json::Value> {
+			let path = str::from_utf8(path).ok()?;
+			path.split('.').filter(|key| !key.is_empty()).try_fold(json, |value, key| match value {
+				serde_json::Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+				_ => value.get(key),
+			})
 		}
 
-		/// Fetch from remote and deserialize the JSON to a struct
-		fn fetch_n_parse() -> Result<GithubInfo, Error<T>> {
-			let resp_bytes = Self::fetch_from_remote().map_err(|e| {
-				log::error!("fetch_from_remote error: {:?}", e);
-				<Error<T>>::HttpFetchingError
-			})?;
-
-			let resp_str = str::from_utf8(&resp_bytes).map_err(|_| <Error<T>>::HttpFetchingError)?;
-			// Print out our fetched JSON string
-			log::info!("{}", resp_str);
-
-			// Deserializing JSON to struct, thanks to `serde` and `serde_derive`
-			let gh_info: GithubInfo =
-			serde_json::from_str(&resp_str).map_err(|_| <Error<T>>::HttpFetchingError)?;
-			Ok(gh_info)
+		/// Parse a decimal such as `"38.4129"` into its integer part and its fraction, to six
+		///   decimal places. Any further decimals are truncated.
+		pub(crate) fn str_to_tuple(s: &str) -> Option<(u64, Permill)> {
+			let mut parts = s.trim().splitn(2, '.');
+			let price_int = parts.next()?.parse::<u64>().ok()?;
+			let decimals = parts.next().unwrap_or("");
+			if !decimals.bytes().all(|digit| digit.is_ascii_digit()) {
+				return None;
+			}
+			let price_decimal = decimals.bytes()
+				.chain(core::iter::repeat(b'0'))
+				.take(6)
+				.fold(0u32, |acc, digit| acc * 10 + (digit - b'0') as u32);
+			Some((price_int, Permill::from_parts(price_decimal)))
 		}
 
-		/// This function uses the `offchain::http` API to query the remote github information,
+		/// This function uses the `offchain::http` API to query `source`,
 		///   and returns the JSON response as vector of bytes.
-		fn fetch_from_remote() -> Result<Vec<u8>, Error<T>> {
-			log::info!("sending request to: {}", HTTP_REMOTE_REQUEST);
+		fn fetch_from_remote(source: &PriceSource) -> Result<Vec<u8>, Error<T>> {
+			let url = str::from_utf8(&source.url).map_err(|_| <Error<T>>::HttpFetchingError)?;
+			log::info!("sending request to: {}", url);
 
 			// Initiate an external HTTP GET request. This is using high-level wrappers from `sp_runtime`.
-			let request = rt_offchain::http::Request::get(HTTP_REMOTE_REQUEST);
+			let mut request = rt_offchain::http::Request::get(url);
+
+			// Some APIs need extra headers, e.g. github requires a `user-agent`.
+			//   See: https://developer.github.com/v3/#user-agent-required
+			for (name, value) in &source.headers {
+				let name = str::from_utf8(name).map_err(|_| <Error<T>>::HttpFetchingError)?;
+				let value = str::from_utf8(value).map_err(|_| <Error<T>>::HttpFetchingError)?;
+				request = request.add_header(name, value);
+			}
 
 			// Keeping the offchain worker execution time reasonable, so limiting the call to be within 3s.
 			let timeout = sp_io::offchain::timestamp()
 			.add(rt_offchain::Duration::from_millis(FETCH_TIMEOUT_PERIOD));
 
-			// For github API request, we also need to specify `user-agent` in http request header.
-			//   See: https://developer.github.com/v3/#user-agent-required
 			let pending = request
-			.add_header("User-Agent", HTTP_HEADER_USER_AGENT)
 				.deadline(timeout) // Setting the timeout time
 				.send() // Sending the request out by the host
 				.map_err(|_| <Error<T>>::HttpFetchingError)?;
@@ -549,19 +741,6 @@ pub mod pallet {
 			Err(<Error<T>>::NoLocalAcctForSigning)
 		}
 
-		fn offchain_unsigned_tx(block_number: T::BlockNumber) -> Result<(), Error<T>> {
-			let number: u64 = block_number.try_into().unwrap_or(0);
-			let call = Call::submit_number_unsigned(number);
-
-			// `submit_unsigned_transaction` returns a type of `Result<(), ()>`
-			//   ref: https://substrate.dev/rustdocs/v2.0.0/frame_system/offchain/struct.SubmitTransaction.html#method.submit_unsigned_transaction
-			SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into())
-			.map_err(|_| {
-				log::error!("Failed in offchain_unsigned_tx");
-				<Error<T>>::OffchainUnsignedTxError
-			})
-		}
-
 		fn offchain_unsigned_tx_signed_payload(block_number: T::BlockNumber) -> Result<(), Error<T>> {
 			// Retrieve the signer to sign the payload
 			let signer = Signer::<T, T::AuthorityId>::any_account();
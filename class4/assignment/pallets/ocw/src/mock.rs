@@ -0,0 +1,170 @@
+use crate as pallet_ocw;
+use crate::PriceSource;
+use frame_support::{parameter_types, traits::GenesisBuild};
+use frame_system as system;
+use sp_core::{
+	offchain::{testing, OffchainDbExt, OffchainWorkerExt, TransactionPoolExt},
+	sr25519::Signature,
+	H256,
+};
+use sp_keystore::{testing::KeyStore, KeystoreExt, SyncCryptoStore};
+use sp_runtime::{
+	testing::{Header, TestXt},
+	traits::{BlakeTwo256, Extrinsic as ExtrinsicT, IdentifyAccount, IdentityLookup, Verify},
+};
+use std::sync::Arc;
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		OcwDemo: pallet_ocw::{Pallet, Call, Storage, Config, Event<T>, ValidateUnsigned},
+	}
+);
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+	pub const SS58Prefix: u8 = 42;
+	pub const MaxPriceSources: u32 = 3;
+	pub const RoundLength: u64 = 3;
+	pub const Quorum: u32 = 4;
+	pub const MinReporters: u32 = 3;
+	pub const MaxReporters: u32 = 4;
+}
+
+impl system::Config for Test {
+	type BaseCallFilter = frame_support::traits::AllowAll;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = sp_core::sr25519::Public;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = ();
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = SS58Prefix;
+	type OnSetCode = ();
+}
+
+pub type Extrinsic = TestXt<Call, ()>;
+pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::AccountId;
+
+impl system::offchain::SigningTypes for Test {
+	type Public = <Signature as Verify>::Signer;
+	type Signature = Signature;
+}
+
+impl<LocalCall> system::offchain::SendTransactionTypes<LocalCall> for Test
+where
+	Call: From<LocalCall>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = Extrinsic;
+}
+
+impl<LocalCall> system::offchain::CreateSignedTransaction<LocalCall> for Test
+where
+	Call: From<LocalCall>,
+{
+	fn create_transaction<C: system::offchain::AppCrypto<Self::Public, Self::Signature>>(
+		call: Call,
+		_public: <Signature as Verify>::Signer,
+		_account: AccountId,
+		nonce: u64,
+	) -> Option<(Call, <Extrinsic as ExtrinsicT>::SignaturePayload)> {
+		Some((call, (nonce, ())))
+	}
+}
+
+impl pallet_ocw::Config for Test {
+	type AuthorityId = pallet_ocw::crypto::TestAuthId;
+	type Call = Call;
+	type Event = Event;
+	type MaxPriceSources = MaxPriceSources;
+	type RoundLength = RoundLength;
+	type Quorum = Quorum;
+	type MinReporters = MinReporters;
+	type ReporterOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxReporters = MaxReporters;
+}
+
+/// One of the genesis reporters, for seeds 1 to 3.
+pub fn reporter(seed: u8) -> AccountId {
+	sp_core::sr25519::Public::from_raw([seed; 32])
+}
+
+/// A source of the price of `asset` on the local mock price server.
+pub fn price_source(asset: &[u8], path: &str) -> PriceSource {
+	PriceSource {
+		asset: asset.to_vec(),
+		url: format!("http://localhost:8000/{}", path).into_bytes(),
+		headers: vec![(b"User-Agent".to_vec(), b"ocw-test".to_vec())],
+		json_path: b"data.priceUsd".to_vec(),
+	}
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_ocw::GenesisConfig::<Test> {
+		price_sources: vec![price_source(b"DOT", "polkadot")],
+		reporters: vec![reporter(3), reporter(1), reporter(2)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	let mut ext = sp_io::TestExternalities::new(t);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// Like `new_test_ext`, with an offchain worker whose HTTP requests are answered from the
+/// returned state, and a keystore holding one offchain worker key.
+pub fn new_offchain_test_ext() -> (
+	sp_io::TestExternalities,
+	Arc<parking_lot::RwLock<testing::OffchainState>>,
+	Arc<parking_lot::RwLock<testing::PoolState>>,
+) {
+	let (offchain, offchain_state) = testing::TestOffchainExt::new();
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let keystore = KeyStore::new();
+	SyncCryptoStore::sr25519_generate_new(&keystore, crate::KEY_TYPE, Some("//Alice")).unwrap();
+
+	let mut ext = new_test_ext();
+	ext.register_extension(OffchainDbExt::new(offchain.clone()));
+	ext.register_extension(OffchainWorkerExt::new(offchain));
+	ext.register_extension(TransactionPoolExt::new(pool));
+	ext.register_extension(KeystoreExt(Arc::new(keystore)));
+	(ext, offchain_state, pool_state)
+}
+
+/// Answer a `GET` of the local `path` with `body`.
+pub fn expect_price_request(state: &mut testing::OffchainState, path: &str, body: &str) {
+	state.expect_request(testing::PendingRequest {
+		method: "GET".into(),
+		uri: format!("http://localhost:8000/{}", path),
+		headers: vec![("User-Agent".into(), "ocw-test".into())],
+		response: Some(body.as_bytes().to_vec()),
+		response_headers: vec![],
+		sent: true,
+		..Default::default()
+	});
+}
@@ -0,0 +1,330 @@
+use crate::{mock::*, Error, Event as OcwEvent, PriceSource};
+use frame_support::{assert_noop, assert_ok, traits::Hooks};
+use parity_scale_codec::Decode;
+use sp_arithmetic::per_things::Permill;
+use sp_runtime::DispatchError;
+
+fn fetch_price(source: &PriceSource) -> Result<(u64, Permill), DispatchError> {
+	OcwDemo::fetch_price(source).map_err(Into::into)
+}
+
+#[test]
+fn set_price_sources_works() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(OcwDemo::price_sources(), vec![price_source(b"DOT", "polkadot")]);
+
+		let sources = vec![price_source(b"DOT", "a"), price_source(b"DOT", "b")];
+		assert_ok!(OcwDemo::set_price_sources(Origin::root(), sources.clone()));
+		assert_eq!(OcwDemo::price_sources(), sources);
+		System::assert_last_event(Event::OcwDemo(OcwEvent::PriceSourcesSet(2)));
+	});
+}
+
+#[test]
+fn set_price_sources_checks_origin_and_sources() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			OcwDemo::set_price_sources(Origin::signed(reporter(1)), vec![]),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			OcwDemo::set_price_sources(Origin::root(), vec![price_source(b"DOT", "a"); 4]),
+			Error::<Test>::TooManyPriceSources
+		);
+		let invalid = PriceSource { json_path: vec![0xff], ..price_source(b"DOT", "a") };
+		assert_noop!(
+			OcwDemo::set_price_sources(Origin::root(), vec![invalid]),
+			Error::<Test>::InvalidPriceSource
+		);
+	});
+}
+
+#[test]
+fn str_to_tuple_works() {
+	assert_eq!(OcwDemo::str_to_tuple("38.4129"), Some((38, Permill::from_parts(412_900))));
+	assert_eq!(OcwDemo::str_to_tuple("18.251938573"), Some((18, Permill::from_parts(251_938))));
+	assert_eq!(OcwDemo::str_to_tuple("7"), Some((7, Permill::zero())));
+	assert_eq!(OcwDemo::str_to_tuple("7."), Some((7, Permill::zero())));
+	assert_eq!(OcwDemo::str_to_tuple("1e5"), None);
+	assert_eq!(OcwDemo::str_to_tuple("-1.5"), None);
+	assert_eq!(OcwDemo::str_to_tuple("1.5x"), None);
+	assert_eq!(OcwDemo::str_to_tuple(""), None);
+}
+
+#[test]
+fn json_at_works() {
+	let json: serde_json::Value =
+		serde_json::from_str(r#"{"data": {"priceUsd": "1.5", "quotes": [{"price": 2}]}}"#).unwrap();
+
+	assert_eq!(OcwDemo::json_at(&json, b"data.priceUsd"), Some(&serde_json::json!("1.5")));
+	assert_eq!(OcwDemo::json_at(&json, b"data.quotes.0.price"), Some(&serde_json::json!(2)));
+	assert_eq!(OcwDemo::json_at(&json, b""), Some(&json));
+	assert_eq!(OcwDemo::json_at(&json, b"data.quotes.1"), None);
+	assert_eq!(OcwDemo::json_at(&json, b"data.missing"), None);
+}
+
+#[test]
+fn fetch_price_reads_json_path() {
+	let (mut ext, state, _) = new_offchain_test_ext();
+	expect_price_request(&mut state.write(), "polkadot", r#"{"data": {"priceUsd": "18.2519"}}"#);
+	expect_price_request(&mut state.write(), "quotes", r#"{"data": [{"usd": 3.25}]}"#);
+	expect_price_request(&mut state.write(), "broken", r#"{"data": {}}"#);
+
+	ext.execute_with(|| {
+		assert_eq!(fetch_price(&price_source(b"DOT", "polkadot")), Ok((18, Permill::from_parts(251_900))));
+		let quotes = PriceSource { json_path: b"data.0.usd".to_vec(), ..price_source(b"DOT", "quotes") };
+		assert_eq!(fetch_price(&quotes), Ok((3, Permill::from_parts(250_000))));
+		assert_eq!(fetch_price(&price_source(b"DOT", "broken")), Err(Error::<Test>::PriceParsingError.into()));
+	});
+}
+
+fn submit(seed: u8, asset: &[u8], price: u64) -> frame_support::dispatch::DispatchResult {
+	OcwDemo::submit_price_signed(Origin::signed(reporter(seed)), asset.to_vec(), (price, Permill::zero()))
+}
+
+fn with_assets(test: impl FnOnce()) {
+	new_test_ext().execute_with(|| {
+		assert_ok!(OcwDemo::set_price_sources(
+			Origin::root(),
+			vec![price_source(b"DOT", "polkadot"), price_source(b"KSM", "kusama")]
+		));
+		test();
+	});
+}
+
+#[test]
+fn median_works() {
+	let price = |int, parts| (int, Permill::from_parts(parts));
+	assert_eq!(OcwDemo::median(&mut []), None);
+	assert_eq!(OcwDemo::median(&mut [price(7, 0)]), Some(price(7, 0)));
+	assert_eq!(OcwDemo::median(&mut [price(9, 0), price(1, 0), price(5, 0)]), Some(price(5, 0)));
+	assert_eq!(OcwDemo::median(&mut [price(2, 0), price(1, 500_000)]), Some(price(1, 750_000)));
+	assert_eq!(OcwDemo::median(&mut [price(1, 900_000), price(2, 200_000)]), Some(price(2, 50_000)));
+	assert_eq!(
+		OcwDemo::median(&mut [price(u64::MAX, 999_999), price(u64::MAX, 999_999)]),
+		Some(price(u64::MAX, 999_999))
+	);
+}
+
+#[test]
+fn quorum_publishes_median() {
+	with_assets(|| {
+		assert_ok!(OcwDemo::add_reporter(Origin::root(), reporter(0)));
+		assert_ok!(submit(1, b"DOT", 10));
+		assert_ok!(submit(2, b"DOT", 1000));
+		System::assert_last_event(Event::OcwDemo(OcwEvent::NewPrice(Some(reporter(2)), b"DOT".to_vec(), (1000, Permill::zero()))));
+		assert!(OcwDemo::prices(b"DOT".to_vec()).is_empty());
+
+		// A reporter's new submission replaces its previous one.
+		assert_ok!(submit(2, b"DOT", 12));
+		assert_eq!(OcwDemo::rounds(b"DOT".to_vec()).unwrap().submissions.len(), 2);
+
+		assert_ok!(submit(3, b"DOT", 11));
+		assert!(OcwDemo::prices(b"DOT".to_vec()).is_empty());
+
+		assert_ok!(submit(0, b"DOT", 13));
+		System::assert_last_event(Event::OcwDemo(OcwEvent::PricePublished(
+			b"DOT".to_vec(),
+			(11, Permill::from_parts(500_000)),
+			vec![reporter(1), reporter(2), reporter(3), reporter(0)],
+		)));
+		assert_eq!(
+			OcwDemo::prices(b"DOT".to_vec()).into_iter().collect::<Vec<_>>(),
+			vec![(11, Permill::from_parts(500_000))]
+		);
+		assert_eq!(OcwDemo::rounds(b"DOT".to_vec()), None);
+	});
+}
+
+#[test]
+fn round_closes_after_round_length() {
+	with_assets(|| {
+		assert_ok!(submit(1, b"DOT", 10));
+		assert_ok!(submit(2, b"DOT", 20));
+		assert_ok!(submit(3, b"DOT", 30));
+		System::set_block_number(3);
+		assert_ok!(submit(1, b"KSM", 300));
+
+		OcwDemo::on_initialize(3);
+		assert!(OcwDemo::prices(b"DOT".to_vec()).is_empty());
+
+		OcwDemo::on_initialize(4);
+		System::assert_last_event(Event::OcwDemo(OcwEvent::PricePublished(
+			b"DOT".to_vec(),
+			(20, Permill::zero()),
+			vec![reporter(1), reporter(2), reporter(3)],
+		)));
+		assert_eq!(OcwDemo::prices(b"DOT".to_vec()).into_iter().collect::<Vec<_>>(), vec![(20, Permill::zero())]);
+		assert_eq!(OcwDemo::rounds(b"DOT".to_vec()), None);
+		assert!(OcwDemo::rounds(b"KSM".to_vec()).is_some());
+
+		// A single reporter cannot publish a price on its own.
+		OcwDemo::on_initialize(6);
+		System::assert_last_event(Event::OcwDemo(OcwEvent::RoundDiscarded(b"KSM".to_vec(), 1)));
+		assert!(OcwDemo::prices(b"KSM".to_vec()).is_empty());
+		assert_eq!(OcwDemo::rounds(b"KSM".to_vec()), None);
+	});
+}
+
+#[test]
+fn prices_keep_bounded_history_per_asset() {
+	with_assets(|| {
+		assert_ok!(OcwDemo::add_reporter(Origin::root(), reporter(0)));
+		for price in 0..12 {
+			for seed in 0..=3 {
+				assert_ok!(submit(seed, b"DOT", price));
+			}
+		}
+		let dot: Vec<u64> = OcwDemo::prices(b"DOT".to_vec()).iter().map(|price| price.0).collect();
+		assert_eq!(dot, (2..12).collect::<Vec<_>>());
+		assert!(OcwDemo::prices(b"KSM".to_vec()).is_empty());
+
+		assert_noop!(submit(1, b"BTC", 1), Error::<Test>::UnknownAsset);
+	});
+}
+
+fn submitted_price(pool_state: &parking_lot::RwLock<sp_core::offchain::testing::PoolState>) -> crate::Call<Test> {
+	let tx = pool_state.write().transactions.pop().unwrap();
+	match Extrinsic::decode(&mut &*tx).unwrap().call {
+		Call::OcwDemo(call) => call,
+		call => panic!("unexpected call {:?}", call),
+	}
+}
+
+#[test]
+fn offchain_worker_falls_back_to_next_source() {
+	let (mut ext, state, pool_state) = new_offchain_test_ext();
+	expect_price_request(&mut state.write(), "down", "<html>Bad Gateway</html>");
+	expect_price_request(&mut state.write(), "polkadot", r#"{"data": {"priceUsd": "18.2519"}}"#);
+
+	ext.execute_with(|| {
+		assert_ok!(OcwDemo::set_price_sources(
+			Origin::root(),
+			vec![price_source(b"DOT", "down"), price_source(b"DOT", "polkadot")]
+		));
+		assert_ok!(OcwDemo::fetch_price_info(0));
+
+		assert_eq!(
+			submitted_price(&pool_state),
+			crate::Call::submit_price_signed(b"DOT".to_vec(), (18, Permill::from_parts(251_900)))
+		);
+	});
+}
+
+#[test]
+fn offchain_worker_takes_assets_in_turn() {
+	let (mut ext, state, pool_state) = new_offchain_test_ext();
+	let sources = vec![price_source(b"DOT", "polkadot"), price_source(b"KSM", "kusama"), price_source(b"DOT", "backup")];
+
+	ext.execute_with(|| {
+		assert_ok!(OcwDemo::set_price_sources(Origin::root(), sources.clone()));
+		for (turn, path, asset, price) in vec![(0, "polkadot", b"DOT", 18), (1, "kusama", b"KSM", 301), (2, "polkadot", b"DOT", 19)] {
+			expect_price_request(&mut state.write(), path, &format!(r#"{{"data": {{"priceUsd": "{}"}}}}"#, price));
+			assert_ok!(OcwDemo::fetch_price_info(turn));
+			assert_eq!(
+				submitted_price(&pool_state),
+				crate::Call::submit_price_signed(asset.to_vec(), (price, Permill::zero()))
+			);
+		}
+	});
+}
+
+#[test]
+fn offchain_worker_fails_without_price_sources() {
+	let (mut ext, _, pool_state) = new_offchain_test_ext();
+
+	ext.execute_with(|| {
+		assert_ok!(OcwDemo::set_price_sources(Origin::root(), vec![]));
+		assert_eq!(
+			OcwDemo::fetch_price_info(0).map_err(DispatchError::from),
+			Err(Error::<Test>::NoPriceAvailable.into())
+		);
+		assert!(pool_state.read().transactions.is_empty());
+	});
+}
+
+#[test]
+fn add_and_remove_reporter_works() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(OcwDemo::reporters(), vec![reporter(1), reporter(2), reporter(3)]);
+
+		assert_ok!(OcwDemo::add_reporter(Origin::root(), reporter(0)));
+		System::assert_last_event(Event::OcwDemo(OcwEvent::ReporterAdded(reporter(0))));
+		assert_eq!(OcwDemo::reporters(), vec![reporter(0), reporter(1), reporter(2), reporter(3)]);
+
+		assert_ok!(OcwDemo::remove_reporter(Origin::root(), reporter(2)));
+		System::assert_last_event(Event::OcwDemo(OcwEvent::ReporterRemoved(reporter(2))));
+		assert_eq!(OcwDemo::reporters(), vec![reporter(0), reporter(1), reporter(3)]);
+	});
+}
+
+#[test]
+fn remove_reporter_drops_open_submissions() {
+	with_assets(|| {
+		assert_ok!(submit(1, b"DOT", 10));
+		assert_ok!(submit(2, b"DOT", 20));
+		assert_ok!(submit(1, b"KSM", 300));
+
+		assert_ok!(OcwDemo::remove_reporter(Origin::root(), reporter(1)));
+		assert_eq!(OcwDemo::rounds(b"DOT".to_vec()).unwrap().submissions, vec![(reporter(2), (20, Permill::zero()))]);
+		assert_eq!(OcwDemo::rounds(b"KSM".to_vec()), None);
+	});
+}
+
+#[test]
+fn add_and_remove_reporter_checks() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(OcwDemo::add_reporter(Origin::signed(reporter(1)), reporter(4)), DispatchError::BadOrigin);
+		assert_noop!(OcwDemo::remove_reporter(Origin::signed(reporter(1)), reporter(2)), DispatchError::BadOrigin);
+
+		assert_noop!(OcwDemo::add_reporter(Origin::root(), reporter(1)), Error::<Test>::AlreadyReporter);
+		assert_noop!(OcwDemo::remove_reporter(Origin::root(), reporter(4)), Error::<Test>::NotReporter);
+
+		assert_ok!(OcwDemo::add_reporter(Origin::root(), reporter(4)));
+		assert_noop!(OcwDemo::add_reporter(Origin::root(), reporter(5)), Error::<Test>::TooManyReporters);
+	});
+}
+
+#[test]
+fn only_reporters_submit() {
+	with_assets(|| {
+		assert_noop!(OcwDemo::submit_number_signed(Origin::signed(reporter(4)), 1), Error::<Test>::NotReporter);
+		assert_noop!(submit(4, b"DOT", 1), Error::<Test>::NotReporter);
+		assert_ok!(OcwDemo::submit_number_signed(Origin::signed(reporter(1)), 1));
+
+		assert_ok!(OcwDemo::remove_reporter(Origin::root(), reporter(1)));
+		assert_noop!(submit(1, b"DOT", 1), Error::<Test>::NotReporter);
+	});
+}
+
+#[test]
+fn validate_unsigned_checks_payload_reporter() {
+	use crate::Payload;
+	use frame_system::offchain::SignedPayload;
+	use sp_runtime::{
+		transaction_validity::{InvalidTransaction, TransactionSource},
+		traits::ValidateUnsigned,
+	};
+
+	let (mut ext, _, _) = new_offchain_test_ext();
+	ext.execute_with(|| {
+		let public = sp_io::crypto::sr25519_public_keys(crate::KEY_TYPE)[0];
+		let payload = Payload { number: 7, public };
+		let signature = <Payload<_> as SignedPayload<Test>>::sign::<crate::crypto::TestAuthId>(&payload).unwrap();
+		let call = crate::Call::submit_number_unsigned_with_signed_payload(payload.clone(), signature.clone());
+
+		assert_eq!(
+			OcwDemo::validate_unsigned(TransactionSource::External, &call),
+			InvalidTransaction::BadSigner.into()
+		);
+		assert_ok!(OcwDemo::add_reporter(Origin::root(), public));
+		assert_ok!(OcwDemo::validate_unsigned(TransactionSource::External, &call));
+
+		// A payload validated before its signer was removed is rejected at dispatch.
+		assert_ok!(OcwDemo::remove_reporter(Origin::root(), public));
+		assert_noop!(
+			OcwDemo::submit_number_unsigned_with_signed_payload(Origin::none(), payload, signature),
+			Error::<Test>::NotReporter
+		);
+	});
+}
@@ -104,10 +104,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 101,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 2,
 };
 
 /// This determines the average expected block time that we are targeting.
@@ -276,11 +276,25 @@ impl pallet_template::Config for Runtime {
 	type Event = Event;
 }
 
+parameter_types! {
+	pub const MaxPriceSources: u32 = 8;
+	pub const PriceRoundLength: BlockNumber = 4;
+	pub const PriceQuorum: u32 = 5;
+	pub const PriceMinReporters: u32 = 3;
+	pub const MaxReporters: u32 = 16;
+}
+
 /// For pallet-ocw
 impl pallet_ocw::Config for Runtime {
 	type AuthorityId = pallet_ocw::crypto::TestAuthId;
 	type Call = Call;
 	type Event = Event;
+	type MaxPriceSources = MaxPriceSources;
+	type RoundLength = PriceRoundLength;
+	type Quorum = PriceQuorum;
+	type MinReporters = PriceMinReporters;
+	type ReporterOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxReporters = MaxReporters;
 }
 
 impl<LocalCall> frame_system::offchain::CreateSignedTransaction<LocalCall> for Runtime
@@ -350,7 +364,7 @@ construct_runtime!(
 		Sudo: pallet_sudo::{Pallet, Call, Config<T>, Storage, Event<T>},
 		// Include the custom logic from the pallet-template in the runtime.
 		TemplateModule: pallet_template::{Pallet, Call, Storage, Event<T>},
-		OcwDemo: pallet_ocw::{Pallet, Call, Storage, Event<T>, ValidateUnsigned},
+		OcwDemo: pallet_ocw::{Pallet, Call, Storage, Config, Event<T>, ValidateUnsigned},
 	}
 );
 
@@ -0,0 +1,28 @@
+#!/usr/bin/env python3
+# Serves fixed prices in the coincap format, at the price sources of the development chain
+# specs, so the offchain worker can be run without internet access.
+#
+# Usage: ./scripts/mock-price-server.py [port]
+import json
+import sys
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+PORT = int(sys.argv[1]) if len(sys.argv) > 1 else 8000
+PRICES = {"polkadot": "18.2519", "kusama": "301.7713", "tether": "1.0002"}
+
+
+class Handler(BaseHTTPRequestHandler):
+    def do_GET(self):
+        asset = self.path.rstrip("/").rsplit("/", 1)[-1]
+        if asset not in PRICES:
+            self.send_error(404)
+            return
+        body = json.dumps({"data": {"id": asset, "priceUsd": PRICES[asset]}}).encode()
+        self.send_response(200)
+        self.send_header("Content-Type", "application/json")
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+
+HTTPServer(("127.0.0.1", PORT), Handler).serve_forever()
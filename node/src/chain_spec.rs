@@ -1,7 +1,7 @@
 use sp_core::{Pair, Public, sr25519};
 use node_template_runtime::{
 	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig,
-	SudoConfig, SystemConfig, WASM_BINARY, Signature
+	SudoConfig, SystemConfig, KittiesModuleConfig, WASM_BINARY, Signature
 };
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_finality_grandpa::AuthorityId as GrandpaId;
@@ -153,5 +153,14 @@ fn testnet_genesis(
 			// Assign network admin rights.
 			key: root_key,
 		},
+		kitties_module: KittiesModuleConfig {
+			// Give Alice and Bob a few kitties, one of each listed for sale.
+			kitties: vec![
+				(get_account_id_from_seed::<sr25519::Public>("Alice"), *b"alice-kitty-0001", None),
+				(get_account_id_from_seed::<sr25519::Public>("Alice"), *b"alice-kitty-0002", Some(1 << 40)),
+				(get_account_id_from_seed::<sr25519::Public>("Bob"), *b"bob-kitty-000001", None),
+				(get_account_id_from_seed::<sr25519::Public>("Bob"), *b"bob-kitty-000002", Some(1 << 40)),
+			],
+		},
 	}
 }
@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use structopt::StructOpt;
 use sc_cli::RunCmd;
 
@@ -8,6 +9,11 @@ pub struct Cli {
 
 	#[structopt(flatten)]
 	pub run: RunCmd,
+
+	/// Index kitty events into a SQLite database at this path and serve
+	/// `kitties_salesOverTime` and `kitties_topSellers` from it.
+	#[structopt(long = "kitty-index", value_name = "PATH", parse(from_os_str))]
+	pub kitty_index: Option<PathBuf>,
 }
 
 #[derive(Debug, StructOpt)]
@@ -35,6 +41,9 @@ pub enum Subcommand {
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
 
+	/// Export kitties from the local database, or import them into a chain spec.
+	Kitties(crate::kitties_cmd::KittiesCmd),
+
 	/// The custom benchmark subcommmand benchmarking runtime pallets.
 	#[structopt(name = "benchmark", about = "Benchmark runtime pallets.")]
 	Benchmark(frame_benchmarking_cli::BenchmarkCmd),
@@ -0,0 +1,239 @@
+//! The `kitties` subcommand: move kitty ownership from one chain to another.
+//!
+//! `kitties export` reads the kitties stored at a block of the local database, without
+//! starting the network, and `kitties import-genesis` writes such an export into the
+//! kitties genesis of a chain spec. Each kitty reserves a deposit at genesis, so the owners
+//! need enough balance in the new spec's balances genesis.
+
+#[cfg(test)]
+mod tests;
+
+use std::{convert::TryFrom, fmt, fs, io::{self, Write}, path::PathBuf, str::FromStr, sync::Arc};
+
+use codec::Decode;
+use node_template_runtime::{opaque::Block, AccountId, Balance, KittyId, KittyInfo};
+use sc_cli::{BlockNumberOrHash, CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use sc_client_api::{Backend, StorageProvider};
+use serde::{Deserialize, Serialize};
+use sp_blockchain::HeaderBackend;
+use sp_core::{crypto::Ss58Codec, hexdisplay::HexDisplay, storage::{StorageData, StorageKey}, twox_128};
+use sp_runtime::generic::BlockId;
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+pub enum KittiesCmd {
+	/// Export the kitties stored at a block of the local database.
+	Export(ExportCmd),
+	/// Write exported kitties into the kitties genesis of a chain spec.
+	ImportGenesis(ImportGenesisCmd),
+}
+
+/// The file format of an export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+	Json,
+	Csv,
+}
+
+impl FromStr for Format {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"json" => Ok(Format::Json),
+			"csv" => Ok(Format::Csv),
+			_ => Err(format!("Unknown format {}, expected json or csv", s)),
+		}
+	}
+}
+
+impl fmt::Display for Format {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Format::Json => write!(f, "json"),
+			Format::Csv => write!(f, "csv"),
+		}
+	}
+}
+
+#[derive(Debug, StructOpt)]
+pub struct ExportCmd {
+	/// The block to export at, by number or hash; the best block if not given.
+	#[structopt(long, value_name = "HASH or NUMBER")]
+	pub at: Option<BlockNumberOrHash>,
+
+	/// `json` or `csv`.
+	#[structopt(long, default_value = "json")]
+	pub format: Format,
+
+	/// Write the export to this file instead of stdout.
+	#[structopt(long, parse(from_os_str))]
+	pub output: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct ImportGenesisCmd {
+	/// A file written by `kitties export`.
+	#[structopt(long, parse(from_os_str))]
+	pub input: PathBuf,
+
+	/// The format of `--input`, `json` or `csv`.
+	#[structopt(long, default_value = "json")]
+	pub format: Format,
+
+	/// The chain spec to update. It must be a plain spec: convert it with `build-spec --raw` afterwards.
+	#[structopt(long = "chain-spec", parse(from_os_str))]
+	pub chain_spec: PathBuf,
+
+	/// Write the updated chain spec here instead of overwriting `--chain-spec`.
+	#[structopt(long, parse(from_os_str))]
+	pub output: Option<PathBuf>,
+}
+
+/// One kitty as exported.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ExportedKitty {
+	pub id: KittyId,
+	#[serde(with = "sp_core::bytes")]
+	pub dna: Vec<u8>,
+	pub owner: AccountId,
+	pub price: Option<Balance>,
+}
+
+impl ExportCmd {
+	pub fn run<C, B>(&self, client: Arc<C>) -> sc_cli::Result<()>
+	where
+		B: Backend<Block>,
+		C: StorageProvider<Block, B> + HeaderBackend<Block>,
+	{
+		let at = match &self.at {
+			Some(at) => at.parse::<Block>()?,
+			None => BlockId::Number(client.info().best_number),
+		};
+		let prefix = StorageKey([twox_128(b"KittiesModule"), twox_128(b"Kitties")].concat());
+		let kitties = decode_kitties(client.storage_pairs(&at, &prefix)?)?;
+
+		match &self.output {
+			Some(path) => write_kitties(&kitties, self.format, fs::File::create(path)?),
+			None => write_kitties(&kitties, self.format, io::stdout()),
+		}
+	}
+}
+
+impl ImportGenesisCmd {
+	pub fn run(&self) -> sc_cli::Result<()> {
+		let kitties = read_kitties(&fs::read_to_string(&self.input)?, self.format)?;
+		let mut spec: serde_json::Value = serde_json::from_slice(&fs::read(&self.chain_spec)?)
+			.map_err(|e| format!("Invalid chain spec: {}", e))?;
+		set_genesis_kitties(&mut spec, &kitties)?;
+
+		let spec = serde_json::to_vec_pretty(&spec).map_err(|e| e.to_string())?;
+		fs::write(self.output.as_ref().unwrap_or(&self.chain_spec), spec)?;
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ExportCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
+/// Decode the `Kitties` map of the kitties pallet, sorted by kitty id.
+fn decode_kitties(pairs: Vec<(StorageKey, StorageData)>) -> Result<Vec<ExportedKitty>, codec::Error> {
+	// Map prefix, then the 16-byte hash of the `Blake2_128Concat` key.
+	const KEY_OFFSET: usize = 32 + 16;
+
+	let mut kitties = pairs.into_iter().map(|(key, value)| {
+		let id = KittyId::decode(&mut key.0.get(KEY_OFFSET..).unwrap_or_default())?;
+		let info = KittyInfo::decode(&mut &value.0[..])?;
+		Ok(ExportedKitty { id, dna: info.dna.0.to_vec(), owner: info.owner, price: info.price })
+	}).collect::<Result<Vec<_>, codec::Error>>()?;
+	kitties.sort_by_key(|kitty| kitty.id);
+
+	Ok(kitties)
+}
+
+fn write_kitties(kitties: &[ExportedKitty], format: Format, mut out: impl Write) -> sc_cli::Result<()> {
+	match format {
+		Format::Json => {
+			serde_json::to_writer_pretty(&mut out, kitties).map_err(|e| e.to_string())?;
+			writeln!(out)?;
+		},
+		Format::Csv => {
+			writeln!(out, "collection,index,dna,owner,price")?;
+			for kitty in kitties {
+				let price = kitty.price.map(|price| price.to_string()).unwrap_or_default();
+				let (collection, index) = kitty.id;
+				writeln!(out, "{},{},0x{},{},{}", collection, index, HexDisplay::from(&kitty.dna), kitty.owner, price)?;
+			}
+		},
+	}
+	Ok(())
+}
+
+fn read_kitties(input: &str, format: Format) -> sc_cli::Result<Vec<ExportedKitty>> {
+	match format {
+		Format::Json => serde_json::from_str(input).map_err(|e| format!("Invalid kitties export: {}", e).into()),
+		Format::Csv => input.lines().skip(1).filter(|line| !line.trim().is_empty()).enumerate().map(|(row, line)| {
+			let invalid = |what: &str| sc_cli::Error::Input(format!("Invalid {} on row {} of the kitties export", what, row + 1));
+			let fields: Vec<_> = line.trim().split(',').collect();
+			if fields.len() != 5 {
+				return Err(invalid("number of fields"));
+			}
+			Ok(ExportedKitty {
+				id: (
+					fields[0].parse().map_err(|_| invalid("collection"))?,
+					fields[1].parse().map_err(|_| invalid("index"))?,
+				),
+				dna: sp_core::bytes::from_hex(fields[2]).map_err(|_| invalid("dna"))?,
+				owner: AccountId::from_ss58check(fields[3]).map_err(|_| invalid("owner"))?,
+				price: match fields[4] {
+					"" => None,
+					price => Some(price.parse().map_err(|_| invalid("price"))?),
+				},
+			})
+		}).collect(),
+	}
+}
+
+/// Replace the kitties genesis of a plain chain spec with `kitties`, in id order.
+///
+/// Genesis kitties are numbered from zero in the common collection, so ids are not kept,
+/// only their order.
+fn set_genesis_kitties(spec: &mut serde_json::Value, kitties: &[ExportedKitty]) -> sc_cli::Result<()> {
+	let mut kitties = kitties.to_vec();
+	kitties.sort_by_key(|kitty| kitty.id);
+	let entries = kitties.into_iter().map(|kitty| {
+		let dna = <[u8; 16]>::try_from(&kitty.dna[..])
+			.map_err(|_| format!("Kitty {:?} has {} bytes of DNA, expected 16", kitty.id, kitty.dna.len()))?;
+		Ok((kitty.owner, dna, kitty.price))
+	}).collect::<Result<Vec<_>, String>>()?;
+
+	let genesis = spec.pointer_mut("/genesis/runtime/kittiesModule")
+		.and_then(|genesis| genesis.as_object_mut())
+		.ok_or("No kitties genesis in the chain spec; is it a raw spec?")?;
+	genesis.insert("kitties".into(), serde_json::to_value(entries).map_err(|e| e.to_string())?);
+
+	Ok(())
+}
@@ -0,0 +1,78 @@
+use super::*;
+use codec::Encode;
+use sp_core::blake2_128;
+use pallet_kitties::{Kitty, KittyInfo as Info};
+
+fn kitty(id: KittyId, seed: u8, price: Option<Balance>) -> ExportedKitty {
+	ExportedKitty { id, dna: vec![seed; 16], owner: AccountId::new([seed; 32]), price }
+}
+
+fn kitties() -> Vec<ExportedKitty> {
+	vec![kitty((0, 0), 1, None), kitty((1, 3), 2, Some(u128::MAX))]
+}
+
+fn export(kitties: &[ExportedKitty], format: Format) -> String {
+	let mut out = Vec::new();
+	write_kitties(kitties, format, &mut out).unwrap();
+	String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn decode_kitties_works() {
+	let prefix = [twox_128(b"KittiesModule"), twox_128(b"Kitties")].concat();
+	let pair = |kitty: &ExportedKitty| {
+		let id = kitty.id.encode();
+		let key = [&prefix[..], &blake2_128(&id), &id].concat();
+		let mut dna = [0u8; 16];
+		dna.copy_from_slice(&kitty.dna);
+		let info = Info {
+			dna: Kitty(dna),
+			owner: kitty.owner.clone(),
+			price: kitty.price,
+			depositor: kitty.owner.clone(),
+			deposit: 0,
+		};
+		(StorageKey(key), StorageData(info.encode()))
+	};
+	let expected = kitties();
+
+	assert_eq!(decode_kitties(expected.iter().rev().map(pair).collect()).unwrap(), expected);
+	assert!(decode_kitties(vec![(StorageKey(prefix), StorageData(vec![]))]).is_err());
+}
+
+#[test]
+fn json_export_round_trips() {
+	let json = export(&kitties(), Format::Json);
+	assert!(json.contains(r#""dna": "0x01010101010101010101010101010101""#));
+	assert_eq!(read_kitties(&json, Format::Json).unwrap(), kitties());
+}
+
+#[test]
+fn csv_export_round_trips() {
+	let csv = export(&kitties(), Format::Csv);
+	assert_eq!(csv.lines().next(), Some("collection,index,dna,owner,price"));
+	assert_eq!(csv.lines().nth(1).unwrap(), format!("0,0,0x{},{},", "01".repeat(16), AccountId::new([1; 32])));
+	assert_eq!(read_kitties(&csv, Format::Csv).unwrap(), kitties());
+
+	assert!(read_kitties("collection,index,dna,owner,price\n0,0,0x01,owner", Format::Csv).is_err());
+	assert!(read_kitties("collection,index,dna,owner,price\n0,x,0x01,owner,", Format::Csv).is_err());
+}
+
+#[test]
+fn set_genesis_kitties_works() {
+	let mut spec = serde_json::json!({
+		"name": "Local Testnet",
+		"genesis": { "runtime": { "kittiesModule": { "kitties": [] } } },
+	});
+	let kitties = vec![kitty((1, 0), 2, Some(100)), kitty((0, 1), 1, None)];
+
+	set_genesis_kitties(&mut spec, &kitties).unwrap();
+	assert_eq!(spec["genesis"]["runtime"]["kittiesModule"]["kitties"], serde_json::json!([
+		[AccountId::new([1; 32]).to_string(), [1; 16], null],
+		[AccountId::new([2; 32]).to_string(), [2; 16], 100],
+	]));
+
+	assert!(set_genesis_kitties(&mut spec, &[ExportedKitty { dna: vec![1; 15], ..kitty((0, 0), 1, None) }]).is_err());
+	let mut raw = serde_json::json!({ "genesis": { "raw": { "top": {} } } });
+	assert!(set_genesis_kitties(&mut raw, &kitties).is_err());
+}
@@ -0,0 +1,175 @@
+//! `kitties_subscribeEvents`: pushes decoded kitty events to clients as blocks come in, so they
+//! don't have to subscribe to every `System::Events` change and decode it themselves.
+
+#[cfg(test)]
+mod tests;
+
+use std::{marker::PhantomData, pin::Pin, sync::Arc};
+
+use futures::{future, stream, FutureExt, SinkExt, Stream, StreamExt};
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{manager::SubscriptionManager, typed::Subscriber, SubscriptionId};
+use node_template_runtime::{opaque::Block, AccountId, BlockNumber, Hash, KittyId};
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use sc_rpc::SubscriptionTaskExecutor;
+use serde::{Deserialize, Serialize};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::traits::Header as HeaderT;
+
+use crate::kitty_index::{block_events, KittyEvent};
+
+/// The kind of kitty event a subscription asks for.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum EventType {
+	Create,
+	Transfer,
+	Sale,
+	Burn,
+}
+
+/// Which events a subscription receives; every field that is set must match.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct EventFilter {
+	/// An account taking part in the event: the creator, either side of a transfer, the seller or buyer,
+	/// or the owner of a burned kitty.
+	pub owner: Option<AccountId>,
+	pub kitty_id: Option<KittyId>,
+	pub event_type: Option<EventType>,
+	/// Only push events once their block is finalized, instead of from every new best block.
+	#[serde(default)]
+	pub finalized: bool,
+}
+
+impl EventFilter {
+	pub fn matches(&self, event: &KittyEvent) -> bool {
+		let (event_type, kitty_id, accounts) = match event {
+			KittyEvent::Created { kitty_id, owner } => (EventType::Create, kitty_id, vec![owner]),
+			KittyEvent::Transferred { kitty_id, from, to } => (EventType::Transfer, kitty_id, vec![from, to]),
+			KittyEvent::Sold { kitty_id, seller, buyer, .. } => (EventType::Sale, kitty_id, vec![seller, buyer]),
+			KittyEvent::Burned { kitty_id, owner } => (EventType::Burn, kitty_id, vec![owner]),
+		};
+
+		self.event_type.map_or(true, |wanted| wanted == event_type)
+			&& self.kitty_id.map_or(true, |wanted| wanted == *kitty_id)
+			&& self.owner.as_ref().map_or(true, |wanted| accounts.contains(&wanted))
+	}
+}
+
+/// One kitty event, pushed to a subscriber.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct KittyEventNotification {
+	pub block_hash: Hash,
+	pub block_number: BlockNumber,
+	/// The position of the event among all of its block's events.
+	pub event_index: u32,
+	pub event: KittyEvent,
+}
+
+#[rpc]
+pub trait KittyEventsApi {
+	/// RPC metadata
+	type Metadata;
+
+	/// Subscribe to the kitty events matching `filter`, or to all of them.
+	#[pubsub(subscription = "kitties_events", subscribe, name = "kitties_subscribeEvents")]
+	fn subscribe_events(
+		&self,
+		metadata: Self::Metadata,
+		subscriber: Subscriber<KittyEventNotification>,
+		filter: Option<EventFilter>,
+	);
+
+	/// Cancel a subscription made with `kitties_subscribeEvents`.
+	#[pubsub(subscription = "kitties_events", unsubscribe, name = "kitties_unsubscribeEvents")]
+	fn unsubscribe_events(&self, metadata: Option<Self::Metadata>, id: SubscriptionId) -> Result<bool>;
+}
+
+/// Implements the `KittyEventsApi` RPC trait by following the client's block notifications.
+pub struct KittyEvents<C, B> {
+	client: Arc<C>,
+	manager: SubscriptionManager,
+	_marker: PhantomData<B>,
+}
+
+impl<C, B> KittyEvents<C, B> {
+	/// Create new `KittyEvents`, running subscriptions on `executor`.
+	pub fn new(client: Arc<C>, executor: SubscriptionTaskExecutor) -> Self {
+		Self { client, manager: SubscriptionManager::new(Arc::new(executor)), _marker: PhantomData }
+	}
+}
+
+type BlockStream = Pin<Box<dyn Stream<Item = (BlockNumber, Hash)> + Send>>;
+
+impl<C, B> KittyEvents<C, B>
+where
+	B: Backend<Block> + Send + Sync + 'static,
+	C: BlockchainEvents<Block> + HeaderBackend<Block> + StorageProvider<Block, B> + Send + Sync + 'static,
+{
+	/// New best blocks as they are imported.
+	fn best_blocks(&self) -> BlockStream {
+		Box::pin(self.client.import_notification_stream()
+			.filter(|notification| future::ready(notification.is_new_best))
+			.map(|notification| (*notification.header.number(), notification.hash)))
+	}
+
+	/// Every finalized block, including those finalized together with a later one.
+	fn finalized_blocks(&self) -> BlockStream {
+		let client = self.client.clone();
+		let mut last = client.info().finalized_number;
+		Box::pin(self.client.finality_notification_stream().flat_map(move |notification| {
+			let number = *notification.header.number();
+			let blocks: Vec<_> = (last + 1..number)
+				.filter_map(|skipped| client.hash(skipped).ok().flatten().map(|hash| (skipped, hash)))
+				.chain(std::iter::once((number, notification.hash)))
+				.collect();
+			last = number;
+			stream::iter(blocks)
+		}))
+	}
+}
+
+impl<C, B> KittyEventsApi for KittyEvents<C, B>
+where
+	B: Backend<Block> + Send + Sync + 'static,
+	C: BlockchainEvents<Block> + HeaderBackend<Block> + StorageProvider<Block, B> + Send + Sync + 'static,
+{
+	type Metadata = sc_rpc::Metadata;
+
+	fn subscribe_events(
+		&self,
+		_metadata: Self::Metadata,
+		subscriber: Subscriber<KittyEventNotification>,
+		filter: Option<EventFilter>,
+	) {
+		let filter = filter.unwrap_or_default();
+		let blocks = if filter.finalized { self.finalized_blocks() } else { self.best_blocks() };
+		let client = self.client.clone();
+
+		let notifications = blocks.flat_map(move |(block_number, block_hash)| {
+			let events = block_events(&*client, block_hash).unwrap_or_else(|e| {
+				log::warn!(target: "kitty-events", "Failed to read the events of #{}: {}", block_number, e);
+				Vec::new()
+			});
+			let notifications: Vec<_> = events.into_iter()
+				.filter(|(_, event)| filter.matches(event))
+				.map(|(event_index, event)| KittyEventNotification { block_hash, block_number, event_index, event })
+				.collect();
+			stream::iter(notifications)
+		});
+
+		self.manager.add(subscriber, |sink| {
+			notifications
+				.map(|notification| Ok::<_, ()>(Ok(notification)))
+				.forward(sink.sink_map_err(|e| log::warn!(target: "kitty-events", "Error sending notifications: {:?}", e)))
+				.map(|_| ())
+		});
+	}
+
+	fn unsubscribe_events(&self, _metadata: Option<Self::Metadata>, id: SubscriptionId) -> Result<bool> {
+		Ok(self.manager.cancel(id))
+	}
+}
@@ -0,0 +1,30 @@
+use super::*;
+use pallet_kitties::SalePrice;
+
+fn account(seed: u8) -> AccountId {
+	AccountId::new([seed; 32])
+}
+
+#[test]
+fn filter_matches_events() {
+	let sale = KittyEvent::Sold { kitty_id: (0, 3), seller: account(1), buyer: account(2), price: SalePrice::Native(10) };
+	let filter = |json: &str| serde_json::from_str::<EventFilter>(json).unwrap();
+
+	assert!(EventFilter::default().matches(&sale));
+	assert!(filter(r#"{"eventType": "sale", "kittyId": [0, 3]}"#).matches(&sale));
+	assert!(!filter(r#"{"eventType": "transfer"}"#).matches(&sale));
+	assert!(!filter(r#"{"kittyId": [1, 3]}"#).matches(&sale));
+
+	let owner = |who: &AccountId| filter(&format!(r#"{{"owner": "{}"}}"#, who));
+	assert!(owner(&account(2)).matches(&sale));
+	assert!(!owner(&account(5)).matches(&sale));
+	assert!(serde_json::from_str::<EventFilter>(r#"{"account": null}"#).is_err());
+}
+
+#[test]
+fn events_are_tagged_by_type() {
+	let event = KittyEvent::Created { kitty_id: (1, 0), owner: account(1) };
+	let json = serde_json::to_value(&event).unwrap();
+	assert_eq!(json["type"], "created");
+	assert_eq!(json["kittyId"], serde_json::json!([1, 0]));
+}
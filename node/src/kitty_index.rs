@@ -0,0 +1,323 @@
+//! An optional SQLite index of `pallet_kitties` events, enabled with `--kitty-index <path>`.
+//!
+//! The worker follows the best chain and records kitties, transfers, sales and burns block by block,
+//! so questions like "sales per day" or "top sellers" become plain queries instead of scans
+//! over raw storage. When the chain reorganises, every row above the last finalized block is
+//! dropped and the new best chain is indexed again from there.
+//!
+//! Kitty ids are stored SCALE encoded, as `(collection, index)`; an index written before kitties
+//! were keyed by collection has to be deleted and rebuilt.
+//!
+//! Kitties minted in the genesis block emit no events and are not in the index. Reading events
+//! of old blocks needs their state, so index a long chain from an archive node.
+
+mod rpc;
+mod worker;
+#[cfg(test)]
+mod tests;
+
+pub use rpc::{KittyIndexApi, KittyIndexRpc};
+pub use worker::{block_events, block_records, run, storage_key, EventRecord};
+
+use std::{collections::BTreeMap, path::Path, sync::Mutex};
+
+use codec::{Decode, Encode};
+use node_template_runtime::{AccountId, Balance, BlockNumber, Hash, KittyId, Runtime};
+use pallet_kitties::SalePrice;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sp_rpc::number::NumberOrHex;
+
+pub type AssetId = pallet_kitties::AssetIdOf<Runtime>;
+pub type Price = pallet_kitties::SalePriceOf<Runtime>;
+
+const SCHEMA: &str = "
+	CREATE TABLE IF NOT EXISTS blocks (
+		number INTEGER PRIMARY KEY,
+		hash BLOB NOT NULL,
+		timestamp INTEGER NOT NULL
+	);
+	CREATE TABLE IF NOT EXISTS meta (
+		key TEXT PRIMARY KEY,
+		value INTEGER NOT NULL
+	);
+	CREATE TABLE IF NOT EXISTS kitties (
+		id BLOB PRIMARY KEY,
+		creator BLOB NOT NULL,
+		owner BLOB NOT NULL,
+		block INTEGER NOT NULL
+	);
+	CREATE TABLE IF NOT EXISTS transfers (
+		block INTEGER NOT NULL,
+		event_index INTEGER NOT NULL,
+		kitty_id BLOB NOT NULL,
+		from_account BLOB NOT NULL,
+		to_account BLOB NOT NULL,
+		PRIMARY KEY (block, event_index)
+	);
+	CREATE TABLE IF NOT EXISTS sales (
+		block INTEGER NOT NULL,
+		event_index INTEGER NOT NULL,
+		kitty_id BLOB NOT NULL,
+		seller BLOB NOT NULL,
+		buyer BLOB NOT NULL,
+		asset_id INTEGER,
+		price TEXT NOT NULL,
+		PRIMARY KEY (block, event_index)
+	);
+	CREATE TABLE IF NOT EXISTS burns (
+		block INTEGER NOT NULL,
+		event_index INTEGER NOT NULL,
+		kitty_id BLOB NOT NULL,
+		creator BLOB NOT NULL,
+		created_block INTEGER NOT NULL,
+		PRIMARY KEY (block, event_index)
+	);
+	CREATE INDEX IF NOT EXISTS sales_by_asset ON sales (asset_id, seller);
+	CREATE VIEW IF NOT EXISTS moves AS
+		SELECT block, event_index, kitty_id, to_account FROM transfers
+		UNION ALL
+		SELECT block, event_index, kitty_id, buyer FROM sales;
+";
+
+/// A `pallet_kitties` event that creates a kitty, moves it to a new owner or burns it.
+#[derive(Serialize, Clone, PartialEq, Debug)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum KittyEvent {
+	#[serde(rename_all = "camelCase")]
+	Created { kitty_id: KittyId, owner: AccountId },
+	#[serde(rename_all = "camelCase")]
+	Transferred { kitty_id: KittyId, from: AccountId, to: AccountId },
+	#[serde(rename_all = "camelCase")]
+	Sold { kitty_id: KittyId, seller: AccountId, buyer: AccountId, price: Price },
+	#[serde(rename_all = "camelCase")]
+	Burned { kitty_id: KittyId, owner: AccountId },
+}
+
+/// The kitty events of one block, each with its position among the block's events.
+#[derive(Clone, PartialEq, Debug)]
+pub struct IndexedBlock {
+	pub number: BlockNumber,
+	pub hash: Hash,
+	/// `pallet_timestamp::Now` at this block, in milliseconds.
+	pub timestamp: u64,
+	pub events: Vec<(u32, KittyEvent)>,
+}
+
+/// Sales within one window of `sales_over_time`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SalesBucket {
+	/// Start of the window, in milliseconds since the Unix epoch.
+	pub start: u64,
+	pub sales: u32,
+	pub volume: NumberOrHex,
+}
+
+/// What one account has sold, as returned by `top_sellers`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SellerStats {
+	pub seller: AccountId,
+	pub sales: u32,
+	pub volume: NumberOrHex,
+}
+
+pub struct KittyIndex {
+	conn: Mutex<Connection>,
+}
+
+impl KittyIndex {
+	/// Open the index at `path`, creating it if needed.
+	pub fn open(path: &Path) -> rusqlite::Result<Self> {
+		Self::with_connection(Connection::open(path)?)
+	}
+
+	#[cfg(test)]
+	pub fn in_memory() -> rusqlite::Result<Self> {
+		Self::with_connection(Connection::open_in_memory()?)
+	}
+
+	fn with_connection(conn: Connection) -> rusqlite::Result<Self> {
+		conn.execute_batch(SCHEMA)?;
+		Ok(Self { conn: Mutex::new(conn) })
+	}
+
+	/// The highest indexed block.
+	pub fn tip(&self) -> rusqlite::Result<Option<(BlockNumber, Hash)>> {
+		let conn = self.conn.lock().expect("kitty index lock poisoned");
+		conn.query_row(
+			"SELECT number, hash FROM blocks ORDER BY number DESC LIMIT 1",
+			[],
+			|row| Ok((row.get(0)?, Hash::from_slice(&row.get::<_, Vec<u8>>(1)?))),
+		).optional()
+	}
+
+	/// The last block known to be final; nothing at or below it is ever reverted.
+	pub fn finalized(&self) -> rusqlite::Result<Option<BlockNumber>> {
+		let conn = self.conn.lock().expect("kitty index lock poisoned");
+		conn.query_row("SELECT value FROM meta WHERE key = 'finalized'", [], |row| row.get(0)).optional()
+	}
+
+	pub fn set_finalized(&self, number: BlockNumber) -> rusqlite::Result<()> {
+		let conn = self.conn.lock().expect("kitty index lock poisoned");
+		conn.execute("INSERT OR REPLACE INTO meta (key, value) VALUES ('finalized', ?1)", params![number])?;
+		Ok(())
+	}
+
+	/// Record a block on top of the current tip.
+	pub fn apply_block(&self, block: &IndexedBlock) -> rusqlite::Result<()> {
+		let mut conn = self.conn.lock().expect("kitty index lock poisoned");
+		let tx = conn.transaction()?;
+		tx.execute(
+			"INSERT OR REPLACE INTO blocks (number, hash, timestamp) VALUES (?1, ?2, ?3)",
+			params![block.number, block.hash.as_bytes(), block.timestamp as i64],
+		)?;
+		for (event_index, event) in &block.events {
+			match event {
+				KittyEvent::Created { kitty_id, owner } => {
+					tx.execute(
+						"INSERT OR REPLACE INTO kitties (id, creator, owner, block) VALUES (?1, ?2, ?2, ?3)",
+						params![kitty_id.encode(), owner.encode(), block.number],
+					)?;
+				},
+				KittyEvent::Transferred { kitty_id, from, to } => {
+					tx.execute(
+						"INSERT INTO transfers (block, event_index, kitty_id, from_account, to_account)
+							VALUES (?1, ?2, ?3, ?4, ?5)",
+						params![block.number, event_index, kitty_id.encode(), from.encode(), to.encode()],
+					)?;
+					tx.execute("UPDATE kitties SET owner = ?1 WHERE id = ?2", params![to.encode(), kitty_id.encode()])?;
+				},
+				KittyEvent::Sold { kitty_id, seller, buyer, price } => {
+					let (asset_id, amount) = match price {
+						SalePrice::Native(amount) => (None, amount),
+						SalePrice::Asset(asset_id, amount) => (Some(asset_id), amount),
+					};
+					tx.execute(
+						"INSERT INTO sales (block, event_index, kitty_id, seller, buyer, asset_id, price)
+							VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+						params![
+							block.number,
+							event_index,
+							kitty_id.encode(),
+							seller.encode(),
+							buyer.encode(),
+							asset_id,
+							amount.to_string(),
+						],
+					)?;
+					tx.execute("UPDATE kitties SET owner = ?1 WHERE id = ?2", params![buyer.encode(), kitty_id.encode()])?;
+				},
+				KittyEvent::Burned { kitty_id, .. } => {
+					// Keep what `revert_to` needs to bring the kitty back.
+					tx.execute(
+						"INSERT INTO burns (block, event_index, kitty_id, creator, created_block)
+							SELECT ?1, ?2, id, creator, block FROM kitties WHERE id = ?3",
+						params![block.number, event_index, kitty_id.encode()],
+					)?;
+					tx.execute("DELETE FROM kitties WHERE id = ?1", params![kitty_id.encode()])?;
+				},
+			}
+		}
+		tx.commit()
+	}
+
+	/// Drop every block above `number`, or everything if `None`, bring back the kitties the
+	/// dropped burns had deleted, and restore the owners the dropped transfers and sales had changed.
+	pub fn revert_to(&self, number: Option<BlockNumber>) -> rusqlite::Result<()> {
+		let mut conn = self.conn.lock().expect("kitty index lock poisoned");
+		let number = number.map_or(-1, i64::from);
+		let tx = conn.transaction()?;
+		tx.execute("DELETE FROM kitties WHERE block > ?1", params![number])?;
+		tx.execute(
+			"INSERT OR REPLACE INTO kitties (id, creator, owner, block)
+				SELECT kitty_id, creator, creator, created_block FROM burns WHERE block > ?1 AND created_block <= ?1",
+			params![number],
+		)?;
+		tx.execute(
+			"UPDATE kitties SET owner = COALESCE(
+				(SELECT to_account FROM moves WHERE kitty_id = kitties.id AND block <= ?1
+					ORDER BY block DESC, event_index DESC LIMIT 1),
+				creator
+			) WHERE id IN (SELECT kitty_id FROM moves WHERE block > ?1 UNION SELECT kitty_id FROM burns WHERE block > ?1)",
+			params![number],
+		)?;
+		tx.execute("DELETE FROM burns WHERE block > ?1", params![number])?;
+		tx.execute("DELETE FROM transfers WHERE block > ?1", params![number])?;
+		tx.execute("DELETE FROM sales WHERE block > ?1", params![number])?;
+		tx.execute("DELETE FROM blocks WHERE number > ?1", params![number])?;
+		tx.commit()
+	}
+
+	/// The indexed owner of `kitty_id`.
+	pub fn owner(&self, kitty_id: KittyId) -> rusqlite::Result<Option<AccountId>> {
+		let conn = self.conn.lock().expect("kitty index lock poisoned");
+		conn.query_row("SELECT owner FROM kitties WHERE id = ?1", params![kitty_id.encode()], |row| row.get(0))
+			.optional()
+			.map(|owner: Option<Vec<u8>>| owner.and_then(|owner| decode_account(&owner)))
+	}
+
+	/// Sales in the native currency, or in `asset_id`, between `from` (inclusive) and `to`
+	/// (exclusive), grouped into `bucket`-long windows. All times are in milliseconds.
+	pub fn sales_over_time(
+		&self,
+		asset_id: Option<AssetId>,
+		bucket: u64,
+		from: u64,
+		to: u64,
+	) -> rusqlite::Result<Vec<SalesBucket>> {
+		let bucket = bucket.max(1);
+		let mut windows = BTreeMap::<u64, (u32, Balance)>::new();
+		let conn = self.conn.lock().expect("kitty index lock poisoned");
+		let mut stmt = conn.prepare(
+			"SELECT blocks.timestamp, sales.price FROM sales JOIN blocks ON blocks.number = sales.block
+				WHERE sales.asset_id IS ?1 AND blocks.timestamp >= ?2 AND blocks.timestamp < ?3",
+		)?;
+		let rows = stmt.query_map(params![asset_id, from as i64, to.min(i64::MAX as u64) as i64], |row| {
+			Ok((row.get::<_, i64>(0)? as u64, row.get::<_, String>(1)?))
+		})?;
+		for row in rows {
+			let (timestamp, price) = row?;
+			let window = windows.entry(timestamp - timestamp % bucket).or_default();
+			window.0 += 1;
+			window.1 = window.1.saturating_add(parse_price(&price));
+		}
+
+		Ok(windows.into_iter()
+			.map(|(start, (sales, volume))| SalesBucket { start, sales, volume: volume.into() })
+			.collect())
+	}
+
+	/// The `limit` accounts with the highest sale volume in the native currency, or in `asset_id`.
+	pub fn top_sellers(&self, asset_id: Option<AssetId>, limit: u32) -> rusqlite::Result<Vec<SellerStats>> {
+		let mut sellers = BTreeMap::<Vec<u8>, (u32, Balance)>::new();
+		let conn = self.conn.lock().expect("kitty index lock poisoned");
+		let mut stmt = conn.prepare("SELECT seller, price FROM sales WHERE asset_id IS ?1")?;
+		let rows = stmt.query_map(params![asset_id], |row| Ok((row.get::<_, Vec<u8>>(0)?, row.get::<_, String>(1)?)))?;
+		for row in rows {
+			let (seller, price) = row?;
+			let stats = sellers.entry(seller).or_default();
+			stats.0 += 1;
+			stats.1 = stats.1.saturating_add(parse_price(&price));
+		}
+
+		let mut sellers: Vec<_> = sellers.into_iter().collect();
+		sellers.sort_by(|(_, a), (_, b)| b.1.cmp(&a.1).then(b.0.cmp(&a.0)));
+		Ok(sellers.into_iter()
+			.filter_map(|(seller, (sales, volume))| {
+				Some(SellerStats { seller: decode_account(&seller)?, sales, volume: volume.into() })
+			})
+			.take(limit as usize)
+			.collect())
+	}
+}
+
+fn decode_account(encoded: &[u8]) -> Option<AccountId> {
+	AccountId::decode(&mut &encoded[..]).ok()
+}
+
+/// Prices are stored as text since SQLite integers stop at `i64`.
+fn parse_price(price: &str) -> Balance {
+	price.parse().unwrap_or_default()
+}
@@ -0,0 +1,84 @@
+//! `kitties_salesOverTime` and `kitties_topSellers`, answered from the local index.
+
+use std::sync::Arc;
+
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+
+use super::{AssetId, KittyIndex, SalesBucket, SellerStats};
+
+/// The number of sellers returned when the caller does not give a limit.
+const DEFAULT_TOP_SELLERS: u32 = 10;
+/// The most sellers a caller can ask for.
+const MAX_TOP_SELLERS: u32 = 100;
+
+#[rpc]
+pub trait KittyIndexApi {
+	/// The number and volume of sales per `bucket` milliseconds between `from` and `to`, in the
+	/// native currency or in `asset_id`. Windows without sales are left out.
+	#[rpc(name = "kitties_salesOverTime")]
+	fn sales_over_time(
+		&self,
+		bucket: u64,
+		from: Option<u64>,
+		to: Option<u64>,
+		asset_id: Option<AssetId>,
+	) -> Result<Vec<SalesBucket>>;
+
+	/// The accounts with the highest sale volume in the native currency or in `asset_id`.
+	#[rpc(name = "kitties_topSellers")]
+	fn top_sellers(&self, limit: Option<u32>, asset_id: Option<AssetId>) -> Result<Vec<SellerStats>>;
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+	/// The query against the index failed.
+	IndexError,
+}
+
+impl From<Error> for i64 {
+	fn from(e: Error) -> i64 {
+		match e {
+			Error::IndexError => 1,
+		}
+	}
+}
+
+/// Implements the `KittyIndexApi` RPC trait by querying the index.
+pub struct KittyIndexRpc {
+	index: Arc<KittyIndex>,
+}
+
+impl KittyIndexRpc {
+	/// Create new `KittyIndexRpc` over the given index.
+	pub fn new(index: Arc<KittyIndex>) -> Self {
+		Self { index }
+	}
+}
+
+fn index_error(e: rusqlite::Error) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(Error::IndexError.into()),
+		message: "Unable to query the kitty index.".into(),
+		data: Some(format!("{:?}", e).into()),
+	}
+}
+
+impl KittyIndexApi for KittyIndexRpc {
+	fn sales_over_time(
+		&self,
+		bucket: u64,
+		from: Option<u64>,
+		to: Option<u64>,
+		asset_id: Option<AssetId>,
+	) -> Result<Vec<SalesBucket>> {
+		self.index
+			.sales_over_time(asset_id, bucket, from.unwrap_or_default(), to.unwrap_or(u64::MAX))
+			.map_err(index_error)
+	}
+
+	fn top_sellers(&self, limit: Option<u32>, asset_id: Option<AssetId>) -> Result<Vec<SellerStats>> {
+		let limit = limit.unwrap_or(DEFAULT_TOP_SELLERS).min(MAX_TOP_SELLERS);
+		self.index.top_sellers(asset_id, limit).map_err(index_error)
+	}
+}
@@ -0,0 +1,163 @@
+use super::*;
+use node_template_runtime::Event;
+
+const DAY: u64 = 24 * 60 * 60 * 1000;
+
+fn account(seed: u8) -> AccountId {
+	AccountId::new([seed; 32])
+}
+
+fn block(number: BlockNumber, fork: u8, events: Vec<KittyEvent>) -> IndexedBlock {
+	IndexedBlock {
+		number,
+		hash: Hash::repeat_byte(fork.wrapping_mul(100).wrapping_add(number as u8)),
+		timestamp: number as u64 * DAY,
+		events: events.into_iter().enumerate().map(|(i, event)| (i as u32, event)).collect(),
+	}
+}
+
+fn created(kitty_id: KittyId, owner: u8) -> KittyEvent {
+	KittyEvent::Created { kitty_id, owner: account(owner) }
+}
+
+fn sold(kitty_id: KittyId, seller: u8, buyer: u8, price: Balance) -> KittyEvent {
+	KittyEvent::Sold { kitty_id, seller: account(seller), buyer: account(buyer), price: SalePrice::Native(price) }
+}
+
+fn transferred(kitty_id: KittyId, from: u8, to: u8) -> KittyEvent {
+	KittyEvent::Transferred { kitty_id, from: account(from), to: account(to) }
+}
+
+fn burned(kitty_id: KittyId, owner: u8) -> KittyEvent {
+	KittyEvent::Burned { kitty_id, owner: account(owner) }
+}
+
+/// Two kitties minted by 1, one sold to 2 on day 2, the other sold to 3 then resold to 1 on day 3.
+fn seeded_index() -> KittyIndex {
+	let index = KittyIndex::in_memory().unwrap();
+	index.apply_block(&block(1, 0, vec![created((0, 0), 1), created((0, 1), 1)])).unwrap();
+	index.apply_block(&block(2, 0, vec![sold((0, 0), 1, 2, 100)])).unwrap();
+	index.apply_block(&block(3, 0, vec![sold((0, 1), 1, 3, 50), sold((0, 1), 3, 1, u128::MAX / 2)])).unwrap();
+	index
+}
+
+#[test]
+fn apply_block_tracks_owners() {
+	let index = seeded_index();
+	index.apply_block(&block(4, 0, vec![transferred((0, 0), 2, 4)])).unwrap();
+
+	assert_eq!(index.owner((0, 0)).unwrap(), Some(account(4)));
+	assert_eq!(index.owner((0, 1)).unwrap(), Some(account(1)));
+	assert_eq!(index.owner((0, 2)).unwrap(), None);
+	assert_eq!(index.tip().unwrap(), Some((4, block(4, 0, vec![]).hash)));
+}
+
+#[test]
+fn revert_to_restores_owners() {
+	let index = seeded_index();
+	index.apply_block(&block(4, 0, vec![created((0, 2), 4), transferred((0, 0), 2, 4)])).unwrap();
+
+	index.revert_to(Some(2)).unwrap();
+	assert_eq!(index.tip().unwrap(), Some((2, block(2, 0, vec![]).hash)));
+	assert_eq!(index.owner((0, 0)).unwrap(), Some(account(2)));
+	assert_eq!(index.owner((0, 1)).unwrap(), Some(account(1)));
+	assert_eq!(index.owner((0, 2)).unwrap(), None);
+	assert_eq!(index.top_sellers(None, 10).unwrap().len(), 1);
+
+	// Index the other fork on top.
+	index.apply_block(&block(3, 1, vec![transferred((0, 1), 1, 5)])).unwrap();
+	assert_eq!(index.owner((0, 1)).unwrap(), Some(account(5)));
+
+	index.revert_to(None).unwrap();
+	assert_eq!(index.tip().unwrap(), None);
+	assert_eq!(index.owner((0, 0)).unwrap(), None);
+}
+
+#[test]
+fn burns_are_reverted() {
+	let index = seeded_index();
+	index.apply_block(&block(4, 0, vec![transferred((0, 0), 2, 4), burned((0, 0), 4)])).unwrap();
+	index.apply_block(&block(5, 0, vec![created((0, 2), 5), burned((0, 2), 5), burned((0, 1), 1)])).unwrap();
+	assert_eq!(index.owner((0, 0)).unwrap(), None);
+	assert_eq!(index.owner((0, 1)).unwrap(), None);
+	assert_eq!(index.owner((0, 2)).unwrap(), None);
+
+	// Kitties burned above the new tip come back with their owner at the tip, unless they were
+	// created above it too.
+	index.revert_to(Some(4)).unwrap();
+	assert_eq!(index.owner((0, 0)).unwrap(), None);
+	assert_eq!(index.owner((0, 1)).unwrap(), Some(account(1)));
+	assert_eq!(index.owner((0, 2)).unwrap(), None);
+
+	index.revert_to(Some(3)).unwrap();
+	assert_eq!(index.owner((0, 0)).unwrap(), Some(account(2)));
+	assert_eq!(index.owner((0, 1)).unwrap(), Some(account(1)));
+}
+
+#[test]
+fn finalized_is_stored() {
+	let index = seeded_index();
+	assert_eq!(index.finalized().unwrap(), None);
+	index.set_finalized(2).unwrap();
+	index.set_finalized(3).unwrap();
+	assert_eq!(index.finalized().unwrap(), Some(3));
+}
+
+#[test]
+fn sales_over_time_works() {
+	let index = seeded_index();
+
+	assert_eq!(index.sales_over_time(None, DAY, 0, u64::MAX).unwrap(), vec![
+		SalesBucket { start: 2 * DAY, sales: 1, volume: 100u128.into() },
+		SalesBucket { start: 3 * DAY, sales: 2, volume: (u128::MAX / 2 + 50).into() },
+	]);
+	assert_eq!(index.sales_over_time(None, 7 * DAY, 0, 3 * DAY).unwrap(), vec![
+		SalesBucket { start: 0, sales: 1, volume: 100u128.into() },
+	]);
+	assert_eq!(index.sales_over_time(Some(0), DAY, 0, u64::MAX).unwrap(), vec![]);
+}
+
+#[test]
+fn top_sellers_works() {
+	let index = seeded_index();
+	index.apply_block(&block(4, 0, vec![KittyEvent::Sold {
+		kitty_id: (0, 0),
+		seller: account(2),
+		buyer: account(4),
+		price: SalePrice::Asset(7, 1_000),
+	}])).unwrap();
+
+	let sellers = index.top_sellers(None, 10).unwrap();
+	assert_eq!(
+		sellers.iter().map(|stats| (stats.seller.clone(), stats.sales)).collect::<Vec<_>>(),
+		vec![(account(3), 1), (account(1), 2)],
+	);
+	assert_eq!(index.top_sellers(None, 1).unwrap().len(), 1);
+	assert_eq!(index.top_sellers(Some(7), 10).unwrap(), vec![
+		SellerStats { seller: account(2), sales: 1, volume: 1_000u128.into() },
+	]);
+}
+
+#[test]
+fn kitty_events_are_picked_out() {
+	let record = |event| frame_system::EventRecord { phase: frame_system::Phase::Initialization, event, topics: vec![] };
+	let records = vec![
+		record(Event::KittiesModule(pallet_kitties::Event::KittyCreate(account(1), (0, 0)))),
+		record(Event::KittiesModule(pallet_kitties::Event::KittySale(account(1), (0, 0), Some(100)))),
+		record(Event::KittiesModule(pallet_kitties::Event::KittySold(account(1), account(2), (0, 0), SalePrice::Native(100)))),
+		record(Event::KittiesModule(pallet_kitties::Event::KittyTransfer(account(1), account(2), (0, 0)))),
+		record(Event::KittiesModule(pallet_kitties::Event::CollectionMint(account(3), 1, 0))),
+		record(Event::KittiesModule(pallet_kitties::Event::BreedCommit(account(2), 0, 5))),
+		record(Event::KittiesModule(pallet_kitties::Event::KittyBred(account(2), 0, (0, 1)))),
+		record(Event::KittiesModule(pallet_kitties::Event::KittyBurn(account(2), (0, 1)))),
+	];
+
+	assert_eq!(worker::kitty_events(records), vec![
+		(0, created((0, 0), 1)),
+		(2, sold((0, 0), 1, 2, 100)),
+		(3, transferred((0, 0), 1, 2)),
+		(4, created((1, 0), 3)),
+		(6, created((0, 1), 2)),
+		(7, burned((0, 1), 2)),
+	]);
+}
@@ -0,0 +1,149 @@
+//! Feeds the index from the client's block import and finality notifications.
+
+use std::{error::Error, sync::Arc};
+
+use codec::Decode;
+use futures::{future, StreamExt};
+use node_template_runtime::{opaque::Block, BlockNumber, Event, Hash};
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use sp_blockchain::HeaderBackend;
+use sp_core::{storage::StorageKey, twox_128};
+use sp_runtime::{generic::BlockId, traits::Header as HeaderT};
+
+use super::{IndexedBlock, KittyEvent, KittyIndex};
+
+pub type EventRecord = frame_system::EventRecord<Event, Hash>;
+
+enum Notification {
+	Imported,
+	Finalized(BlockNumber),
+}
+
+/// Keep `index` in step with the client's best chain until the node shuts down.
+pub async fn run<C, B>(client: Arc<C>, index: Arc<KittyIndex>)
+where
+	B: Backend<Block>,
+	C: BlockchainEvents<Block> + HeaderBackend<Block> + StorageProvider<Block, B> + Send + Sync + 'static,
+{
+	let imports = client.import_notification_stream()
+		.filter(|notification| future::ready(notification.is_new_best))
+		.map(|_| Notification::Imported);
+	let finality = client.finality_notification_stream()
+		.map(|notification| Notification::Finalized(*notification.header.number()));
+	let mut notifications = futures::stream::select(imports, finality);
+
+	// Catch up with whatever was imported while the index was not running.
+	if let Err(e) = sync(&*client, &index) {
+		log::warn!(target: "kitty-index", "Failed to catch up with the chain: {}", e);
+	}
+
+	while let Some(notification) = notifications.next().await {
+		if let Err(e) = sync(&*client, &index) {
+			log::warn!(target: "kitty-index", "Failed to index new blocks: {}", e);
+			continue;
+		}
+		if let Notification::Finalized(number) = notification {
+			// `sync` left the index on the best chain, which contains the finalized block.
+			let result = index.tip().and_then(|tip| match tip {
+				Some((tip, _)) => index.set_finalized(number.min(tip)),
+				None => Ok(()),
+			});
+			if let Err(e) = result {
+				log::warn!(target: "kitty-index", "Failed to record finality of #{}: {}", number, e);
+			}
+		}
+	}
+}
+
+/// Index every block up to the client's best, first reverting to the last finalized block
+/// if the indexed tip is no longer on the best chain.
+fn sync<C, B>(client: &C, index: &KittyIndex) -> Result<(), Box<dyn Error>>
+where
+	B: Backend<Block>,
+	C: HeaderBackend<Block> + StorageProvider<Block, B>,
+{
+	let next = match index.tip()? {
+		Some((number, hash)) if client.hash(number)? == Some(hash) => number + 1,
+		Some((number, _)) => {
+			let finalized = index.finalized()?;
+			log::info!(
+				target: "kitty-index",
+				"Indexed block #{} left the best chain, reverting to #{}",
+				number,
+				finalized.unwrap_or_default(),
+			);
+			index.revert_to(finalized)?;
+			finalized.map_or(1, |finalized| finalized + 1)
+		},
+		// The genesis block has no events.
+		None => 1,
+	};
+
+	let now_key = storage_key(b"Timestamp", b"Now");
+	for number in next..=client.info().best_number {
+		let hash = match client.hash(number)? {
+			Some(hash) => hash,
+			None => break,
+		};
+		let timestamp = read(client, hash, &now_key)?.unwrap_or_default();
+		index.apply_block(&IndexedBlock { number, hash, timestamp, events: block_events(client, hash)? })?;
+	}
+
+	Ok(())
+}
+
+/// The kitty events deposited in block `hash`.
+pub fn block_events<C, B>(client: &C, hash: Hash) -> Result<Vec<(u32, KittyEvent)>, Box<dyn Error>>
+where
+	B: Backend<Block>,
+	C: StorageProvider<Block, B>,
+{
+	Ok(kitty_events(block_records(client, hash)?))
+}
+
+/// All events deposited in block `hash`, read from its `System::Events`.
+pub fn block_records<C, B>(client: &C, hash: Hash) -> Result<Vec<EventRecord>, Box<dyn Error>>
+where
+	B: Backend<Block>,
+	C: StorageProvider<Block, B>,
+{
+	Ok(read(client, hash, &storage_key(b"System", b"Events"))?.unwrap_or_default())
+}
+
+/// The events of a block the index keeps, with their position among all of the block's events.
+pub(super) fn kitty_events(records: Vec<EventRecord>) -> Vec<(u32, KittyEvent)> {
+	use pallet_kitties::Event::*;
+
+	records.into_iter().enumerate().filter_map(|(event_index, record)| {
+		let event = match record.event {
+			Event::KittiesModule(KittyCreate(owner, kitty_id)) => KittyEvent::Created { kitty_id, owner },
+			Event::KittiesModule(KittyBred(owner, _, kitty_id)) => KittyEvent::Created { kitty_id, owner },
+			Event::KittiesModule(CollectionMint(owner, collection_id, index)) => {
+				KittyEvent::Created { kitty_id: (collection_id, index), owner }
+			},
+			Event::KittiesModule(KittyTransfer(from, to, kitty_id)) => KittyEvent::Transferred { kitty_id, from, to },
+			Event::KittiesModule(KittySold(seller, buyer, kitty_id, price)) => {
+				KittyEvent::Sold { kitty_id, seller, buyer, price }
+			},
+			Event::KittiesModule(KittyBurn(owner, kitty_id)) => KittyEvent::Burned { kitty_id, owner },
+			_ => return None,
+		};
+		Some((event_index as u32, event))
+	}).collect()
+}
+
+pub fn storage_key(pallet: &[u8], item: &[u8]) -> StorageKey {
+	StorageKey([twox_128(pallet), twox_128(item)].concat())
+}
+
+fn read<C, B, T>(client: &C, hash: Hash, key: &StorageKey) -> Result<Option<T>, Box<dyn Error>>
+where
+	B: Backend<Block>,
+	C: StorageProvider<Block, B>,
+	T: Decode,
+{
+	match client.storage(&BlockId::Hash(hash), key)? {
+		Some(data) => Ok(Some(T::decode(&mut &data.0[..])?)),
+		None => Ok(None),
+	}
+}
@@ -0,0 +1,158 @@
+//! Prometheus metrics for the kitty marketplace, updated from every new best block.
+//!
+//! Blocks are counted as they become best, so a block that is later reorganised away is
+//! still counted; over any useful window the rates are unaffected.
+
+#[cfg(test)]
+mod tests;
+
+use std::{error::Error, sync::Arc};
+
+use futures::{future, StreamExt};
+use node_template_runtime::{opaque::Block, Balance, Event, Hash};
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use sp_runtime::generic::BlockId;
+use substrate_prometheus_endpoint::{
+	register, Counter, CounterVec, Gauge, GaugeVec, Opts, PrometheusError, Registry, F64, U64,
+};
+
+use crate::kitty_index::{block_records, storage_key, EventRecord};
+
+/// What happened to kitties in one block.
+#[derive(Default, Clone, PartialEq, Debug)]
+pub struct BlockActivity {
+	pub minted: u64,
+	pub bred: u64,
+	pub transferred: u64,
+	pub sold: u64,
+	/// The sum of the native-currency sales.
+	pub volume: Balance,
+	/// Whether the block had any kitty event, which may have listed or delisted a kitty.
+	pub listings_changed: bool,
+}
+
+impl BlockActivity {
+	pub fn from_records(records: Vec<EventRecord>) -> Self {
+		use pallet_kitties::{Event::*, SalePrice};
+
+		let mut activity = Self::default();
+		for record in records {
+			let event = match record.event {
+				Event::KittiesModule(event) => event,
+				_ => continue,
+			};
+			match event {
+				KittyCreate(..) | CollectionMint(..) => activity.minted += 1,
+				KittyBred(..) => activity.bred += 1,
+				KittyTransfer(..) => activity.transferred += 1,
+				KittySold(_, _, _, price) => {
+					activity.sold += 1;
+					if let SalePrice::Native(price) = price {
+						activity.volume = activity.volume.saturating_add(price);
+					}
+				},
+				_ => {},
+			}
+			activity.listings_changed = true;
+		}
+		activity
+	}
+}
+
+pub struct Metrics {
+	events: CounterVec<U64>,
+	block_events: GaugeVec<U64>,
+	sale_volume: Counter<F64>,
+	listings: Gauge<U64>,
+}
+
+impl Metrics {
+	pub fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			events: register(
+				CounterVec::new(
+					Opts::new("kitties_events_total", "Kitty events in imported best blocks"),
+					&["kind"],
+				)?,
+				registry,
+			)?,
+			block_events: register(
+				GaugeVec::new(
+					Opts::new("kitties_block_events", "Kitty events in the latest best block"),
+					&["kind"],
+				)?,
+				registry,
+			)?,
+			sale_volume: register(
+				Counter::new("kitties_sale_volume_total", "Native-currency volume of kitty sales")?,
+				registry,
+			)?,
+			listings: register(
+				Gauge::new("kitties_active_listings", "Kitties listed for sale, for the native currency or an asset")?,
+				registry,
+			)?,
+		})
+	}
+
+	pub fn observe(&self, activity: &BlockActivity) {
+		for &(kind, count) in [
+			("minted", activity.minted),
+			("bred", activity.bred),
+			("transferred", activity.transferred),
+			("sold", activity.sold),
+		].iter() {
+			self.events.with_label_values(&[kind]).inc_by(count);
+			self.block_events.with_label_values(&[kind]).set(count);
+		}
+		self.sale_volume.inc_by(activity.volume as f64);
+	}
+
+	pub fn set_listings(&self, listings: u64) {
+		self.listings.set(listings);
+	}
+}
+
+/// Update `metrics` from each new best block until the node shuts down.
+pub async fn run<C, B>(client: Arc<C>, metrics: Metrics)
+where
+	B: Backend<Block>,
+	C: BlockchainEvents<Block> + StorageProvider<Block, B> + Send + Sync + 'static,
+{
+	let mut blocks = client.import_notification_stream()
+		.filter(|notification| future::ready(notification.is_new_best));
+	let mut listings_known = false;
+
+	while let Some(notification) = blocks.next().await {
+		let activity = match block_records(&*client, notification.hash) {
+			Ok(records) => BlockActivity::from_records(records),
+			Err(e) => {
+				log::warn!(target: "kitty-metrics", "Failed to read the events of {}: {}", notification.hash, e);
+				continue;
+			},
+		};
+		metrics.observe(&activity);
+
+		// Counting listings reads every listing key, so only do it when they may have changed.
+		if activity.listings_changed || !listings_known {
+			match count_listings(&*client, notification.hash) {
+				Ok(listings) => {
+					metrics.set_listings(listings);
+					listings_known = true;
+				},
+				Err(e) => log::warn!(target: "kitty-metrics", "Failed to count kitty listings: {}", e),
+			}
+		}
+	}
+}
+
+fn count_listings<C, B>(client: &C, hash: Hash) -> Result<u64, Box<dyn Error>>
+where
+	B: Backend<Block>,
+	C: StorageProvider<Block, B>,
+{
+	let at = BlockId::Hash(hash);
+	let native = client.storage_keys(&at, &storage_key(b"KittiesModule", b"Listings"))?.len() as u64;
+	let asset = client.storage_keys(&at, &storage_key(b"KittiesModule", b"KittiesAssetPrice"))?.len() as u64;
+
+	Ok(native + asset)
+}
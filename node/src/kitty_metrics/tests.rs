@@ -0,0 +1,55 @@
+use super::*;
+use node_template_runtime::AccountId;
+use pallet_kitties::SalePrice;
+
+fn record(event: pallet_kitties::Event<node_template_runtime::Runtime>) -> EventRecord {
+	frame_system::EventRecord {
+		phase: frame_system::Phase::ApplyExtrinsic(0),
+		event: Event::KittiesModule(event),
+		topics: vec![],
+	}
+}
+
+#[test]
+fn activity_counts_kitty_events() {
+	use pallet_kitties::Event::*;
+
+	let (alice, bob) = (AccountId::new([1; 32]), AccountId::new([2; 32]));
+	let records = vec![
+		record(KittyCreate(alice.clone(), (0, 0))),
+		record(CollectionMint(alice.clone(), 1, 0)),
+		record(BreedCommit(alice.clone(), 0, 5)),
+		record(KittyBred(alice.clone(), 0, (0, 2))),
+		record(KittyTransfer(alice.clone(), bob.clone(), (0, 0))),
+		record(KittySold(alice.clone(), bob.clone(), (0, 1), SalePrice::Native(300))),
+		record(KittySold(bob.clone(), alice.clone(), (0, 1), SalePrice::Asset(0, 1_000))),
+	];
+
+	assert_eq!(BlockActivity::from_records(records), BlockActivity {
+		minted: 2,
+		bred: 1,
+		transferred: 1,
+		sold: 2,
+		volume: 300,
+		listings_changed: true,
+	});
+	assert_eq!(BlockActivity::from_records(vec![]), BlockActivity::default());
+}
+
+#[test]
+fn metrics_follow_blocks() {
+	let registry = Registry::new();
+	let metrics = Metrics::register(&registry).unwrap();
+
+	metrics.observe(&BlockActivity { minted: 2, sold: 1, volume: 300, ..Default::default() });
+	metrics.observe(&BlockActivity { minted: 1, ..Default::default() });
+	metrics.set_listings(4);
+
+	assert_eq!(metrics.events.with_label_values(&["minted"]).get(), 3);
+	assert_eq!(metrics.events.with_label_values(&["sold"]).get(), 1);
+	assert_eq!(metrics.block_events.with_label_values(&["minted"]).get(), 1);
+	assert_eq!(metrics.block_events.with_label_values(&["sold"]).get(), 0);
+	assert_eq!(metrics.sale_volume.get(), 300.0);
+	assert_eq!(metrics.listings.get(), 4);
+	assert!(Metrics::register(&registry).is_err());
+}
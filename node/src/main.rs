@@ -7,6 +7,10 @@ mod service;
 mod cli;
 mod command;
 mod rpc;
+mod kitty_index;
+mod kitty_events;
+mod kitties_cmd;
+mod kitty_metrics;
 
 fn main() -> sc_cli::Result<()> {
 	command::run()
@@ -7,12 +7,15 @@
 
 use std::sync::Arc;
 
-use node_template_runtime::{opaque::Block, AccountId, Balance, Index};
+use node_template_runtime::{opaque::Block, AccountId, Balance, Index, KittyId, KittyInfo, KittyHistoryEntry, Phenotype};
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::{Error as BlockChainError, HeaderMetadata, HeaderBackend};
 use sp_block_builder::BlockBuilder;
 pub use sc_rpc_api::DenyUnsafe;
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use sc_rpc::SubscriptionTaskExecutor;
 use sp_transaction_pool::TransactionPool;
+use crate::kitty_index::KittyIndex;
 
 
 /// Full client dependencies.
@@ -23,28 +26,40 @@ pub struct FullDeps<C, P> {
 	pub pool: Arc<P>,
 	/// Whether to deny unsafe calls
 	pub deny_unsafe: DenyUnsafe,
+	/// Executor to drive the subscription manager.
+	pub subscription_executor: SubscriptionTaskExecutor,
+	/// The kitty event index, if the node was started with `--kitty-index`.
+	pub kitty_index: Option<Arc<KittyIndex>>,
 }
 
 /// Instantiate all full RPC extensions.
-pub fn create_full<C, P>(
+pub fn create_full<C, P, B>(
 	deps: FullDeps<C, P>,
 ) -> jsonrpc_core::IoHandler<sc_rpc::Metadata> where
+	B: Backend<Block> + Send + Sync + 'static,
 	C: ProvideRuntimeApi<Block>,
 	C: HeaderBackend<Block> + HeaderMetadata<Block, Error=BlockChainError> + 'static,
+	C: BlockchainEvents<Block> + StorageProvider<Block, B>,
 	C: Send + Sync + 'static,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+	C::Api: pallet_kitties_rpc::KittiesRuntimeApi<Block, KittyId, AccountId, KittyInfo, Phenotype, KittyHistoryEntry>,
 	C::Api: BlockBuilder<Block>,
 	P: TransactionPool + 'static,
 {
 	use substrate_frame_rpc_system::{FullSystem, SystemApi};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
+	use pallet_kitties_rpc::{Kitties, KittiesApi};
+	use crate::kitty_index::{KittyIndexApi, KittyIndexRpc};
+	use crate::kitty_events::{KittyEvents, KittyEventsApi};
 
 	let mut io = jsonrpc_core::IoHandler::default();
 	let FullDeps {
 		client,
 		pool,
 		deny_unsafe,
+		subscription_executor,
+		kitty_index,
 	} = deps;
 
 	io.extend_with(
@@ -55,6 +70,18 @@ pub fn create_full<C, P>(
 		TransactionPaymentApi::to_delegate(TransactionPayment::new(client.clone()))
 	);
 
+	io.extend_with(
+		KittiesApi::to_delegate(Kitties::new(client.clone()))
+	);
+
+	io.extend_with(
+		KittyEventsApi::to_delegate(KittyEvents::new(client.clone(), subscription_executor))
+	);
+
+	if let Some(index) = kitty_index {
+		io.extend_with(KittyIndexApi::to_delegate(KittyIndexRpc::new(index)));
+	}
+
 	// Extend this RPC with a custom API by using the following syntax.
 	// `YourRpcStruct` should have a reference to a client, which is needed
 	// to call into the runtime.
@@ -1,6 +1,6 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
-use std::{sync::Arc, time::Duration};
+use std::{path::PathBuf, sync::Arc, time::Duration};
 use sc_client_api::{ExecutorProvider, RemoteBackend};
 use node_template_runtime::{self, opaque::Block, RuntimeApi};
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
@@ -125,7 +125,7 @@ fn remote_keystore(_url: &String) -> Result<Arc<LocalKeystore>, &'static str> {
 }
 
 /// Builds a new service for a full client.
-pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError> {
+pub fn new_full(mut config: Configuration, kitty_index: Option<PathBuf>) -> Result<TaskManager, ServiceError> {
 	let sc_service::PartialComponents {
 		client,
 		backend,
@@ -173,15 +173,40 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	let enable_grandpa = !config.disable_grandpa;
 	let prometheus_registry = config.prometheus_registry().cloned();
 
+	if let Some(registry) = prometheus_registry.as_ref() {
+		let metrics = crate::kitty_metrics::Metrics::register(registry)?;
+		task_manager.spawn_handle().spawn_blocking(
+			"kitty-metrics",
+			crate::kitty_metrics::run(client.clone(), metrics),
+		);
+	}
+
+	let kitty_index = match kitty_index {
+		Some(path) => {
+			let index = crate::kitty_index::KittyIndex::open(&path)
+				.map_err(|e| ServiceError::Other(
+					format!("Error opening kitty index at {}: {}", path.display(), e)))?;
+			let index = Arc::new(index);
+			task_manager.spawn_handle().spawn_blocking(
+				"kitty-index",
+				crate::kitty_index::run(client.clone(), index.clone()),
+			);
+			Some(index)
+		},
+		None => None,
+	};
+
 	let rpc_extensions_builder = {
 		let client = client.clone();
 		let pool = transaction_pool.clone();
 
-		Box::new(move |deny_unsafe, _| {
+		Box::new(move |deny_unsafe, subscription_executor| {
 			let deps = crate::rpc::FullDeps {
 				client: client.clone(),
 				pool: pool.clone(),
 				deny_unsafe,
+				subscription_executor,
+				kitty_index: kitty_index.clone(),
 			};
 
 			crate::rpc::create_full(deps)
@@ -0,0 +1,504 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+    use frame_support::{pallet_prelude::*, transactional, traits::{Randomness, ReservableCurrency, Currency, BalanceStatus}};
+    use frame_system::pallet_prelude::*;
+    use sp_runtime::{RuntimeDebug, traits::{One, Saturating}};
+    use sp_std::{cmp::Reverse, prelude::*};
+    use codec::{Encode, Decode};
+    use pallet_kitties::{Kitty, BalanceOf, KittyIdOf};
+
+    /// An open challenge from one kitty to another, waiting for the opponent to accept.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+    pub struct Challenge<AccountId, KittyId, Balance> {
+        pub challenger: AccountId,
+        pub kitty_id: KittyId,
+        pub opponent_id: KittyId,
+        /// The amount each side puts up; the winner takes both.
+        pub stake: Balance,
+    }
+
+    /// Win/loss record of a kitty.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+    pub struct BattleRecord {
+        pub wins: u32,
+        pub losses: u32,
+    }
+
+    #[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+    pub enum TournamentStatus {
+        /// Accepting entrants until `registration_end`.
+        Registering,
+        /// Bracket seeded; one round is played every `RoundDuration` blocks.
+        Running,
+    }
+
+    /// A knockout tournament paid for by its entrants' fees.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+    pub struct Tournament<AccountId, KittyId, Balance, BlockNumber> {
+        /// The amount each entrant reserves; all of it goes to the champion.
+        pub entry_fee: Balance,
+        pub registration_end: BlockNumber,
+        /// The block at which the next round is played.
+        pub next_round: BlockNumber,
+        /// The number of rounds played so far.
+        pub round: u32,
+        pub status: TournamentStatus,
+        /// Every registered kitty with the account that paid its fee.
+        pub entrants: Vec<(AccountId, KittyId)>,
+        /// Kitties still in the running, paired off two by two each round.
+        pub bracket: Vec<(AccountId, KittyId)>,
+    }
+
+    pub type ChallengeOf<T> = Challenge<
+        <T as frame_system::Config>::AccountId,
+        KittyIdOf<T>,
+        BalanceOf<T>,
+    >;
+
+    pub type TournamentOf<T> = Tournament<
+        <T as frame_system::Config>::AccountId,
+        KittyIdOf<T>,
+        BalanceOf<T>,
+        <T as frame_system::Config>::BlockNumber,
+    >;
+
+    #[pallet::config]
+    pub trait Config: pallet_kitties::Config {
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+        /// The origin allowed to open tournaments.
+        type TournamentOrigin: EnsureOrigin<Self::Origin>;
+        /// The number of blocks between two tournament rounds.
+        type RoundDuration: Get<Self::BlockNumber>;
+        /// The maximum number of kitties in one tournament.
+        type MaxEntrants: Get<u32>;
+        /// The number of blocks between accepting a challenge and the battle being fought.
+        type BattleDelay: Get<Self::BlockNumber>;
+        /// The maximum number of battles fought in the same block.
+        type MaxBattlesPerBlock: Get<u32>;
+    }
+
+    #[pallet::event]
+    #[pallet::metadata(T::AccountId = "AccountId")]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        ChallengeCreate(T::AccountId, u64, KittyIdOf<T>, KittyIdOf<T>, BalanceOf<T>),
+        ChallengeCancel(u64),
+        /// Both stakes are in and both kitties locked until the battle. [challenge_id, battle block]
+        ChallengeAccept(u64, T::BlockNumber),
+        /// [challenge_id, winner kitty, loser kitty]
+        BattleResult(u64, KittyIdOf<T>, KittyIdOf<T>),
+        /// [tournament_id, entry_fee, registration_end]
+        TournamentOpen(u32, BalanceOf<T>, T::BlockNumber),
+        TournamentRegister(u32, T::AccountId, KittyIdOf<T>),
+        /// [tournament_id, round, kitties left]
+        TournamentRound(u32, u32, u32),
+        /// [tournament_id, champion owner, champion kitty, prize]
+        TournamentWin(u32, T::AccountId, KittyIdOf<T>, BalanceOf<T>),
+        /// Not enough entrants; all fees were refunded. [tournament_id]
+        TournamentCancel(u32),
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        ChallengesCountOverflow,
+        NotOwner,
+        SameKitty,
+        InvalidKittyIndex,
+        InvalidChallengeId,
+        BalanceLitter,
+        TournamentsCountOverflow,
+        InvalidTournamentId,
+        RegistrationClosed,
+        TournamentFull,
+        AlreadyRegistered,
+        ChallengeAccepted,
+        TooManyBattles,
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    pub struct Pallet<T>(_);
+
+    #[pallet::storage]
+    #[pallet::getter(fn challenges_count)]
+    pub type ChallengesCount<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn challenges)]
+    pub type Challenges<T: Config> = StorageMap<_, Blake2_128Concat, u64, ChallengeOf<T>>;
+
+    /// Accepted challenges with the opponent's owner, waiting for their battle block.
+    #[pallet::storage]
+    #[pallet::getter(fn accepted)]
+    pub type Accepted<T: Config> = StorageMap<_, Blake2_128Concat, u64, T::AccountId>;
+
+    /// The accepted challenges fought at a given block.
+    #[pallet::storage]
+    #[pallet::getter(fn pending_battles)]
+    pub type PendingBattles<T: Config> = StorageMap<_, Twox64Concat, T::BlockNumber, Vec<u64>, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn records)]
+    pub type Records<T: Config> = StorageMap<_, Blake2_128Concat, KittyIdOf<T>, BattleRecord, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn tournaments_count)]
+    pub type TournamentsCount<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn tournaments)]
+    pub type Tournaments<T: Config> = StorageMap<_, Blake2_128Concat, u32, TournamentOf<T>>;
+
+    /// The tournaments whose registration closes or next round is played at a given block.
+    #[pallet::storage]
+    #[pallet::getter(fn tournaments_due)]
+    pub type TournamentsDue<T: Config> = StorageMap<_, Twox64Concat, T::BlockNumber, Vec<u32>, ValueQuery>;
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(now: T::BlockNumber) -> Weight {
+            let battles = PendingBattles::<T>::take(now);
+            let mut weight = T::DbWeight::get().reads_writes(1 + 4 * battles.len() as u64, 1 + 6 * battles.len() as u64);
+            for challenge_id in battles {
+                Self::fight(challenge_id);
+            }
+
+            let due = TournamentsDue::<T>::take(now);
+            weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+
+            for tournament_id in due {
+                if let Some(tournament) = Self::tournaments(tournament_id) {
+                    weight = weight.saturating_add(
+                        T::DbWeight::get().reads_writes(tournament.bracket.len() as u64 + 1, tournament.entrants.len() as u64 + 2)
+                    );
+                    Self::advance_tournament(tournament_id, tournament, now);
+                }
+            }
+
+            weight
+        }
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        //发起挑战并锁定押金
+        #[pallet::weight(0)]
+        pub fn challenge(
+            origin: OriginFor<T>,
+            kitty_id: KittyIdOf<T>,
+            opponent_id: KittyIdOf<T>,
+            stake: BalanceOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(kitty_id != opponent_id, Error::<T>::SameKitty);
+            ensure!(Some(who.clone()) == pallet_kitties::Pallet::<T>::owner(kitty_id), Error::<T>::NotOwner);
+            ensure!(pallet_kitties::Kitties::<T>::get(opponent_id).is_some(), Error::<T>::InvalidKittyIndex);
+
+            let challenge_id = Self::challenges_count();
+            ensure!(challenge_id != u64::max_value(), Error::<T>::ChallengesCountOverflow);
+            <T as pallet_kitties::Config>::Currency::reserve(&who, stake).map_err(|_| Error::<T>::BalanceLitter)?;
+
+            Challenges::<T>::insert(challenge_id, Challenge {
+                challenger: who.clone(),
+                kitty_id,
+                opponent_id,
+                stake,
+            });
+            ChallengesCount::<T>::put(challenge_id + 1);
+
+            Self::deposit_event(Event::ChallengeCreate(who, challenge_id, kitty_id, opponent_id, stake));
+
+            Ok(())
+        }
+
+        //应战，锁定双方kitty，战斗在若干区块之后用届时的随机数决出胜负
+        #[pallet::weight(0)]
+        #[transactional]
+        pub fn accept(origin: OriginFor<T>, challenge_id: u64) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let challenge = Self::challenges(challenge_id).ok_or(Error::<T>::InvalidChallengeId)?;
+            ensure!(!Accepted::<T>::contains_key(challenge_id), Error::<T>::ChallengeAccepted);
+            ensure!(Some(who.clone()) == pallet_kitties::Pallet::<T>::owner(challenge.opponent_id), Error::<T>::NotOwner);
+            ensure!(
+                Some(challenge.challenger.clone()) == pallet_kitties::Pallet::<T>::owner(challenge.kitty_id),
+                Error::<T>::NotOwner
+            );
+
+            let battle_at = <frame_system::Pallet<T>>::block_number().saturating_add(T::BattleDelay::get());
+            let scheduled = PendingBattles::<T>::decode_len(battle_at).unwrap_or(0);
+            ensure!(scheduled < T::MaxBattlesPerBlock::get() as usize, Error::<T>::TooManyBattles);
+
+            <T as pallet_kitties::Config>::Currency::reserve(&who, challenge.stake)
+                .map_err(|_| Error::<T>::BalanceLitter)?;
+            pallet_kitties::Pallet::<T>::lock(challenge.kitty_id)?;
+            pallet_kitties::Pallet::<T>::lock(challenge.opponent_id)?;
+
+            Accepted::<T>::insert(challenge_id, &who);
+            PendingBattles::<T>::append(battle_at, challenge_id);
+
+            Self::deposit_event(Event::ChallengeAccept(challenge_id, battle_at));
+
+            Ok(())
+        }
+
+        //取消挑战，挑战者或被挑战者都可以调用
+        #[pallet::weight(0)]
+        pub fn cancel(origin: OriginFor<T>, challenge_id: u64) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let challenge = Self::challenges(challenge_id).ok_or(Error::<T>::InvalidChallengeId)?;
+            ensure!(!Accepted::<T>::contains_key(challenge_id), Error::<T>::ChallengeAccepted);
+            ensure!(
+                who == challenge.challenger
+                    || Some(who.clone()) == pallet_kitties::Pallet::<T>::owner(challenge.opponent_id),
+                Error::<T>::NotOwner
+            );
+
+            <T as pallet_kitties::Config>::Currency::unreserve(&challenge.challenger, challenge.stake);
+            Challenges::<T>::remove(challenge_id);
+
+            Self::deposit_event(Event::ChallengeCancel(challenge_id));
+
+            Ok(())
+        }
+
+        //开启锦标赛
+        #[pallet::weight(0)]
+        pub fn open_tournament(
+            origin: OriginFor<T>,
+            entry_fee: BalanceOf<T>,
+            registration_period: T::BlockNumber,
+        ) -> DispatchResult {
+            T::TournamentOrigin::ensure_origin(origin)?;
+
+            let tournament_id = Self::tournaments_count();
+            ensure!(tournament_id != u32::max_value(), Error::<T>::TournamentsCountOverflow);
+            let registration_end = <frame_system::Pallet<T>>::block_number().saturating_add(registration_period);
+
+            Tournaments::<T>::insert(tournament_id, Tournament {
+                entry_fee,
+                registration_end,
+                next_round: registration_end,
+                round: 0,
+                status: TournamentStatus::Registering,
+                entrants: Vec::new(),
+                bracket: Vec::new(),
+            });
+            TournamentsCount::<T>::put(tournament_id + 1);
+            TournamentsDue::<T>::append(registration_end, tournament_id);
+
+            Self::deposit_event(Event::TournamentOpen(tournament_id, entry_fee, registration_end));
+
+            Ok(())
+        }
+
+        //报名锦标赛，锁定报名费和kitty，kitty被淘汰或比赛结束时解锁
+        #[pallet::weight(0)]
+        #[transactional]
+        pub fn register(origin: OriginFor<T>, tournament_id: u32, kitty_id: KittyIdOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(Some(who.clone()) == pallet_kitties::Pallet::<T>::owner(kitty_id), Error::<T>::NotOwner);
+            Tournaments::<T>::try_mutate(tournament_id, |maybe_tournament| -> DispatchResult {
+                let tournament = maybe_tournament.as_mut().ok_or(Error::<T>::InvalidTournamentId)?;
+                ensure!(
+                    tournament.status == TournamentStatus::Registering
+                        && <frame_system::Pallet<T>>::block_number() < tournament.registration_end,
+                    Error::<T>::RegistrationClosed
+                );
+                ensure!(tournament.entrants.len() < T::MaxEntrants::get() as usize, Error::<T>::TournamentFull);
+                ensure!(tournament.entrants.iter().all(|(_, id)| *id != kitty_id), Error::<T>::AlreadyRegistered);
+
+                <T as pallet_kitties::Config>::Currency::reserve(&who, tournament.entry_fee)
+                    .map_err(|_| Error::<T>::BalanceLitter)?;
+                pallet_kitties::Pallet::<T>::lock(kitty_id)?;
+                tournament.entrants.push((who.clone(), kitty_id));
+                tournament.bracket.push((who.clone(), kitty_id));
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::TournamentRegister(tournament_id, who, kitty_id));
+
+            Ok(())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Fighting strength derived from the phenotype: body and fur attack, eyes and mouth defend.
+        pub fn strength(kitty: &Kitty) -> u32 {
+            let p = kitty.phenotype();
+            p.body as u32 + p.fur as u32 + p.eyes as u32 + p.mouth as u32 + p.accessory as u32 / 2
+        }
+
+        fn strength_of(kitty_id: KittyIdOf<T>) -> u32 {
+            pallet_kitties::Kitties::<T>::get(kitty_id).map_or(0, |kitty| Self::strength(&kitty.dna))
+        }
+
+        /// Each side rolls a random bonus up to 255 on top of its strength; ties go to the defender.
+        fn attacker_wins(subject: &[u8], attack: u32, defence: u32) -> bool {
+            let (seed, _) = <T as pallet_kitties::Config>::Randomness::random(subject);
+            let roll = seed.as_ref();
+            attack + roll[0] as u32 > defence + roll[1] as u32
+        }
+
+        /// Fight an accepted challenge with the randomness of the current block, which neither
+        /// side could know when the challenge was accepted, and pay the winner both stakes.
+        fn fight(challenge_id: u64) {
+            let (challenge, opponent) = match (Challenges::<T>::take(challenge_id), Accepted::<T>::take(challenge_id)) {
+                (Some(challenge), Some(opponent)) => (challenge, opponent),
+                _ => return,
+            };
+
+            let subject = (b"kitty-battle", challenge_id).encode();
+            let challenger_wins = Self::attacker_wins(
+                &subject,
+                Self::strength_of(challenge.kitty_id),
+                Self::strength_of(challenge.opponent_id),
+            );
+            let (winner, loser) = if challenger_wins {
+                ((challenge.challenger, challenge.kitty_id), (opponent, challenge.opponent_id))
+            } else {
+                ((opponent, challenge.opponent_id), (challenge.challenger, challenge.kitty_id))
+            };
+
+            <T as pallet_kitties::Config>::Currency::unreserve(&winner.0, challenge.stake);
+            let _ = <T as pallet_kitties::Config>::Currency::repatriate_reserved(
+                &loser.0,
+                &winner.0,
+                challenge.stake,
+                BalanceStatus::Free,
+            );
+            pallet_kitties::Pallet::<T>::unlock(challenge.kitty_id);
+            pallet_kitties::Pallet::<T>::unlock(challenge.opponent_id);
+            Self::record_result(winner.1, loser.1);
+
+            Self::deposit_event(Event::BattleResult(challenge_id, winner.1, loser.1));
+        }
+
+        fn record_result(winner: KittyIdOf<T>, loser: KittyIdOf<T>) {
+            Records::<T>::mutate(winner, |record| record.wins = record.wins.saturating_add(1));
+            Records::<T>::mutate(loser, |record| record.losses = record.losses.saturating_add(1));
+        }
+
+        /// Move a tournament forward if it is due at `now`, and schedule it again for its next
+        /// round unless it is over.
+        fn advance_tournament(tournament_id: u32, mut tournament: TournamentOf<T>, now: T::BlockNumber) {
+            match tournament.status {
+                TournamentStatus::Registering if now >= tournament.registration_end => {
+                    if tournament.entrants.len() < 2 {
+                        for (who, kitty_id) in tournament.entrants.iter() {
+                            <T as pallet_kitties::Config>::Currency::unreserve(who, tournament.entry_fee);
+                            pallet_kitties::Pallet::<T>::unlock(*kitty_id);
+                        }
+                        Tournaments::<T>::remove(tournament_id);
+                        Self::deposit_event(Event::TournamentCancel(tournament_id));
+                        return;
+                    }
+                    tournament.bracket = Self::seed(tournament.bracket);
+                    tournament.status = TournamentStatus::Running;
+                },
+                TournamentStatus::Running if now >= tournament.next_round => {
+                    tournament.round += 1;
+                    tournament.bracket = Self::play_round(tournament_id, tournament.round, &tournament.bracket);
+
+                    if let [(champion, kitty_id)] = &tournament.bracket[..] {
+                        pallet_kitties::Pallet::<T>::unlock(*kitty_id);
+                        let prize = Self::pay_prize(&tournament, champion);
+                        Tournaments::<T>::remove(tournament_id);
+                        Self::deposit_event(Event::TournamentWin(tournament_id, champion.clone(), *kitty_id, prize));
+                        return;
+                    }
+                    Self::deposit_event(Event::TournamentRound(
+                        tournament_id,
+                        tournament.round,
+                        tournament.bracket.len() as u32,
+                    ));
+                },
+                _ => {
+                    TournamentsDue::<T>::append(tournament.next_round, tournament_id);
+                    return;
+                },
+            }
+            tournament.next_round = now.saturating_add(T::RoundDuration::get().max(One::one()));
+            TournamentsDue::<T>::append(tournament.next_round, tournament_id);
+            Tournaments::<T>::insert(tournament_id, tournament);
+        }
+
+        /// Order entrants so the strongest meets the weakest in the first round.
+        fn seed(mut entrants: Vec<(T::AccountId, KittyIdOf<T>)>) -> Vec<(T::AccountId, KittyIdOf<T>)> {
+            entrants.sort_by_key(|(_, kitty_id)| Reverse(Self::strength_of(*kitty_id)));
+            let mut bracket = Vec::with_capacity(entrants.len());
+            let (mut low, mut high) = (0, entrants.len());
+            while low < high {
+                bracket.push(entrants[low].clone());
+                low += 1;
+                if low < high {
+                    high -= 1;
+                    bracket.push(entrants[high].clone());
+                }
+            }
+            bracket
+        }
+
+        /// Fight every adjacent pair in the bracket and release the losers; an unpaired kitty gets a bye.
+        fn play_round(
+            tournament_id: u32,
+            round: u32,
+            bracket: &[(T::AccountId, KittyIdOf<T>)],
+        ) -> Vec<(T::AccountId, KittyIdOf<T>)> {
+            let mut winners = Vec::with_capacity((bracket.len() + 1) / 2);
+            for (index, pair) in bracket.chunks(2).enumerate() {
+                match pair {
+                    [first, second] => {
+                        let subject = (b"kitty-tournament", tournament_id, round, index as u32).encode();
+                        let first_wins = Self::attacker_wins(
+                            &subject,
+                            Self::strength_of(first.1),
+                            Self::strength_of(second.1),
+                        );
+                        let (winner, loser) = if first_wins { (first, second) } else { (second, first) };
+                        Self::record_result(winner.1, loser.1);
+                        pallet_kitties::Pallet::<T>::unlock(loser.1);
+                        winners.push(winner.clone());
+                    },
+                    [bye] => winners.push(bye.clone()),
+                    _ => {},
+                }
+            }
+            winners
+        }
+
+        /// Hand every entry fee to the champion, returning the prize.
+        fn pay_prize(tournament: &TournamentOf<T>, champion: &T::AccountId) -> BalanceOf<T> {
+            let mut prize = BalanceOf::<T>::default();
+            for (who, _) in tournament.entrants.iter() {
+                // Both calls return the part of the fee they could not move.
+                let leftover = if who == champion {
+                    <T as pallet_kitties::Config>::Currency::unreserve(who, tournament.entry_fee)
+                } else {
+                    match <T as pallet_kitties::Config>::Currency::repatriate_reserved(
+                        who,
+                        champion,
+                        tournament.entry_fee,
+                        BalanceStatus::Free,
+                    ) {
+                        Ok(leftover) => leftover,
+                        Err(_) => tournament.entry_fee,
+                    }
+                };
+                prize = prize.saturating_add(tournament.entry_fee.saturating_sub(leftover));
+            }
+            prize
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use crate as pallet_battles;
+use frame_support::{parameter_types, traits::Hooks};
+
+pallet_kitties::kitty_test_runtime! {
+	BattleModule: pallet_battles::{Pallet, Call, Storage, Event<T>},
+}
+
+parameter_types! {
+	pub const RoundDuration: u64 = 5;
+	pub const MaxEntrants: u32 = 4;
+	pub const BattleDelay: u64 = 2;
+	pub const MaxBattlesPerBlock: u32 = 1;
+}
+
+impl pallet_battles::Config for Test {
+	type Event = Event;
+	type TournamentOrigin = frame_system::EnsureRoot<u64>;
+	type RoundDuration = RoundDuration;
+	type MaxEntrants = MaxEntrants;
+	type BattleDelay = BattleDelay;
+	type MaxBattlesPerBlock = MaxBattlesPerBlock;
+}
+
+pub fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		System::set_block_number(System::block_number() + 1);
+		BattleModule::on_initialize(System::block_number());
+	}
+}
@@ -0,0 +1,214 @@
+use crate::{Error, Event, mock::*};
+use frame_support::{assert_ok, assert_noop};
+use super::*;
+use frame_support::traits::{Currency, ReservableCurrency};
+
+#[test]
+fn challenge_works() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1, 2]);
+		assert_ok!(BattleModule::challenge(Origin::signed(1), (0, 0), (0, 1), 500));
+		System::assert_has_event(mock::Event::BattleModule(Event::ChallengeCreate(1, 0, (0, 0), (0, 1), 500)));
+		assert_eq!(Balances::reserved_balance(1), 1_000 + 500);
+	})
+}
+
+#[test]
+fn challenge_failed() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1, 2]);
+		assert_noop!(BattleModule::challenge(Origin::signed(1), (0, 0), (0, 0), 500), Error::<Test>::SameKitty);
+		assert_noop!(BattleModule::challenge(Origin::signed(2), (0, 0), (0, 1), 500), Error::<Test>::NotOwner);
+		assert_noop!(BattleModule::challenge(Origin::signed(1), (0, 0), (0, 5), 500), Error::<Test>::InvalidKittyIndex);
+		assert_noop!(BattleModule::challenge(Origin::signed(1), (0, 0), (0, 1), 200_000_000), Error::<Test>::BalanceLitter);
+	})
+}
+
+#[test]
+fn accept_pays_winner() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1, 2]);
+		assert_ok!(BattleModule::challenge(Origin::signed(1), (0, 0), (0, 1), 500));
+		assert_ok!(BattleModule::accept(Origin::signed(2), 0));
+		System::assert_has_event(mock::Event::BattleModule(Event::ChallengeAccept(0, 3)));
+		//战斗之前双方kitty都被锁定，结果尚未决出
+		assert!(KittyModule::is_locked((0, 0)));
+		assert!(KittyModule::is_locked((0, 1)));
+		assert_eq!(Balances::reserved_balance(1), 1_000 + 500);
+		assert_eq!(Balances::reserved_balance(2), 1_000 + 500);
+		assert_eq!(BattleModule::records((0, 0)).wins + BattleModule::records((0, 0)).losses, 0);
+
+		run_to_block(3);
+		assert!(!KittyModule::is_locked((0, 0)));
+		assert!(!KittyModule::is_locked((0, 1)));
+		assert!(BattleModule::accepted(0).is_none());
+		let (winner, loser) = if BattleModule::records((0, 0)).wins == 1 { (1, 2) } else { (2, 1) };
+		assert_eq!(Balances::free_balance(winner), 100_000_000 - 1_000 + 500);
+		assert_eq!(Balances::free_balance(loser), 100_000_000 - 1_000 - 500);
+		assert_eq!(Balances::reserved_balance(winner), 1_000);
+		assert_eq!(Balances::reserved_balance(loser), 1_000);
+
+		let record_0 = BattleModule::records((0, 0));
+		let record_1 = BattleModule::records((0, 1));
+		assert_eq!(record_0.wins + record_1.wins, 1);
+		assert_eq!(record_0.losses + record_1.losses, 1);
+		assert!(BattleModule::challenges(0).is_none());
+	})
+}
+
+#[test]
+fn accept_failed() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1, 2]);
+		assert_noop!(BattleModule::accept(Origin::signed(2), 0), Error::<Test>::InvalidChallengeId);
+		assert_ok!(BattleModule::challenge(Origin::signed(1), (0, 0), (0, 1), 500));
+		assert_noop!(BattleModule::accept(Origin::signed(1), 0), Error::<Test>::NotOwner);
+		//同一区块的战斗已满
+		create_kitties(&[1, 2]);
+		assert_ok!(BattleModule::challenge(Origin::signed(1), (0, 2), (0, 3), 500));
+		assert_ok!(BattleModule::accept(Origin::signed(2), 1));
+		assert_noop!(BattleModule::accept(Origin::signed(2), 1), Error::<Test>::ChallengeAccepted);
+		assert_noop!(BattleModule::accept(Origin::signed(2), 0), Error::<Test>::TooManyBattles);
+		//挑战者的kitty已经转走
+		assert_ok!(KittyModule::transfer(Origin::signed(1), 0, (0, 0)));
+		assert_noop!(BattleModule::accept(Origin::signed(2), 0), Error::<Test>::NotOwner);
+	})
+}
+
+#[test]
+fn cancel_works() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1, 2]);
+		assert_ok!(BattleModule::challenge(Origin::signed(1), (0, 0), (0, 1), 500));
+		assert_noop!(BattleModule::cancel(Origin::signed(0), 0), Error::<Test>::NotOwner);
+		assert_ok!(BattleModule::cancel(Origin::signed(2), 0));
+		assert_eq!(Balances::reserved_balance(1), 1_000);
+		assert!(BattleModule::challenges(0).is_none());
+
+		//已应战的挑战不能取消
+		assert_ok!(BattleModule::challenge(Origin::signed(1), (0, 0), (0, 1), 500));
+		assert_ok!(BattleModule::accept(Origin::signed(2), 1));
+		assert_noop!(BattleModule::cancel(Origin::signed(1), 1), Error::<Test>::ChallengeAccepted);
+	})
+}
+
+#[test]
+fn open_tournament_works() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(BattleModule::open_tournament(Origin::signed(1), 100, 10), sp_runtime::DispatchError::BadOrigin);
+		assert_ok!(BattleModule::open_tournament(Origin::root(), 100, 10));
+		System::assert_has_event(mock::Event::BattleModule(Event::TournamentOpen(0, 100, 11)));
+		assert_eq!(BattleModule::tournaments_due(11), vec![0]);
+	})
+}
+
+#[test]
+fn register_failed() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1, 2]);
+		assert_noop!(BattleModule::register(Origin::signed(1), 0, (0, 0)), Error::<Test>::InvalidTournamentId);
+		assert_ok!(BattleModule::open_tournament(Origin::root(), 100, 10));
+		assert_noop!(BattleModule::register(Origin::signed(2), 0, (0, 0)), Error::<Test>::NotOwner);
+		assert_ok!(BattleModule::register(Origin::signed(1), 0, (0, 0)));
+		assert!(KittyModule::is_locked((0, 0)));
+		assert_noop!(BattleModule::register(Origin::signed(1), 0, (0, 0)), Error::<Test>::AlreadyRegistered);
+		assert_noop!(KittyModule::transfer(Origin::signed(1), 2, (0, 0)), pallet_kitties::Error::<Test>::KittyLocked);
+		create_kitties(&[2, 2, 2]);
+		assert_ok!(BattleModule::register(Origin::signed(2), 0, (0, 1)));
+		assert_ok!(BattleModule::register(Origin::signed(2), 0, (0, 2)));
+		assert_ok!(BattleModule::register(Origin::signed(2), 0, (0, 3)));
+		assert_noop!(BattleModule::register(Origin::signed(2), 0, (0, 4)), Error::<Test>::TournamentFull);
+		run_to_block(11);
+		assert_noop!(BattleModule::register(Origin::signed(1), 0, (0, 0)), Error::<Test>::RegistrationClosed);
+	})
+}
+
+#[test]
+fn tournament_lifecycle_works() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[0, 1, 2, 0]);
+		let total: u128 = (0..3).map(|who| Balances::total_balance(&who)).sum();
+
+		assert_ok!(BattleModule::open_tournament(Origin::root(), 100, 10));
+		for kitty_id in (0..4).map(|index| (0, index)) {
+			let owner = KittyModule::owner(kitty_id).unwrap();
+			assert_ok!(BattleModule::register(Origin::signed(owner), 0, kitty_id));
+		}
+
+		//报名结束，完成种子排位
+		run_to_block(11);
+		let tournament = BattleModule::tournaments(0).unwrap();
+		assert_eq!(tournament.status, TournamentStatus::Running);
+		assert_eq!(tournament.bracket.len(), 4);
+
+		assert_eq!(BattleModule::tournaments_due(16), vec![0]);
+		assert!((0..4).all(|index| KittyModule::is_locked((0, index))));
+
+		//第一轮，被淘汰的kitty解锁
+		run_to_block(16);
+		System::assert_has_event(mock::Event::BattleModule(Event::TournamentRound(0, 1, 2)));
+		assert_eq!((0..4).filter(|index| KittyModule::is_locked((0, *index))).count(), 2);
+
+		//决赛
+		run_to_block(21);
+		assert!(BattleModule::tournaments(0).is_none());
+		assert!(BattleModule::tournaments_due(26).is_empty());
+		//比赛结束后所有kitty都已解锁
+		assert!((0..4).all(|index| !KittyModule::is_locked((0, index))));
+
+		let wins: u32 = (0..4).map(|index| BattleModule::records((0, index)).wins).sum();
+		let losses: u32 = (0..4).map(|index| BattleModule::records((0, index)).losses).sum();
+		assert_eq!((wins, losses), (3, 3));
+		let champion = (0..4).map(|index| (0, index)).find(|id| BattleModule::records(*id).wins == 2).unwrap();
+		System::assert_has_event(mock::Event::BattleModule(Event::TournamentWin(
+			0,
+			KittyModule::owner(champion).unwrap(),
+			champion,
+			400,
+		)));
+
+		//报名费全部转给冠军，只剩下kitty押金
+		assert_eq!((0..3).map(|who| Balances::total_balance(&who)).sum::<u128>(), total);
+		assert_eq!(Balances::reserved_balance(0), 2_000);
+		assert_eq!(Balances::reserved_balance(1), 1_000);
+		assert_eq!(Balances::reserved_balance(2), 1_000);
+	})
+}
+
+#[test]
+fn tournament_prize_counts_only_paid_fees() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1, 2]);
+		assert_ok!(BattleModule::open_tournament(Origin::root(), 100, 10));
+		assert_ok!(BattleModule::register(Origin::signed(1), 0, (0, 0)));
+		assert_ok!(BattleModule::register(Origin::signed(2), 0, (0, 1)));
+		//双方的保留余额都被罚没到只剩一半报名费
+		Balances::slash_reserved(&1, 1_050);
+		Balances::slash_reserved(&2, 1_050);
+
+		run_to_block(16);
+		let champion = if BattleModule::records((0, 0)).wins == 1 { (0, 0) } else { (0, 1) };
+		System::assert_has_event(mock::Event::BattleModule(Event::TournamentWin(
+			0,
+			KittyModule::owner(champion).unwrap(),
+			champion,
+			100,
+		)));
+	})
+}
+
+#[test]
+fn tournament_without_enough_entrants_is_cancelled() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1, 2]);
+		assert_ok!(BattleModule::open_tournament(Origin::root(), 100, 10));
+		assert_ok!(BattleModule::register(Origin::signed(1), 0, (0, 0)));
+		assert_eq!(Balances::reserved_balance(1), 1_100);
+
+		run_to_block(11);
+		System::assert_has_event(mock::Event::BattleModule(Event::TournamentCancel(0)));
+		assert_eq!(Balances::reserved_balance(1), 1_000);
+		assert!(BattleModule::tournaments(0).is_none());
+		assert!(!KittyModule::is_locked((0, 0)));
+	})
+}
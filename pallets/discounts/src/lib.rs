@@ -0,0 +1,294 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+pub mod migrations;
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+    use frame_support::{pallet_prelude::*, traits::Currency, weights::{DispatchInfo, PostDispatchInfo}};
+    use frame_system::pallet_prelude::*;
+    use sp_runtime::{
+        Permill, RuntimeDebug, SaturatedConversion,
+        traits::{SignedExtension, DispatchInfoOf, PostDispatchInfoOf, Dispatchable, Zero},
+        transaction_validity::{TransactionPriority, TransactionValidity, TransactionValidityError, ValidTransaction},
+    };
+    use codec::{Encode, Decode};
+    use sp_std::{fmt, marker::PhantomData, prelude::*};
+    use pallet_kitties::{BalanceOf, KittyIdOf, traits::{OnKittyCreated, OnKittyTransferred, OnKittyBurned}};
+
+    /// The storage layout of the pallet.
+    #[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+    pub enum Releases {
+        /// `Holdings` only counts kitties created or moved since the pallet was added.
+        V1,
+        /// `Holdings` counts every kitty with a deposit, backfilled from the kitties pallet, and
+        /// `CountedKitties` records which ones.
+        V2,
+    }
+
+    impl Default for Releases {
+        fn default() -> Self {
+            Releases::V1
+        }
+    }
+
+    /// The perks of holding at least `min_kitties` kitties.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+    pub struct DiscountTier {
+        pub min_kitties: u32,
+        /// The share of the transaction fee refunded after dispatch.
+        pub discount: Permill,
+        /// Added to the transaction's priority in the pool.
+        pub priority: TransactionPriority,
+    }
+
+    #[pallet::config]
+    pub trait Config: pallet_kitties::Config + pallet_transaction_payment::Config {
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+        /// The origin allowed to change the discount tiers.
+        type DiscountOrigin: EnsureOrigin<Self::Origin>;
+        /// The maximum number of discount tiers.
+        type MaxTiers: Get<u32>;
+    }
+
+    #[pallet::event]
+    #[pallet::metadata(T::AccountId = "AccountId")]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// [number of tiers]
+        TiersSet(u32),
+        /// Part of a transaction fee was refunded to a kitty holder. [who, amount]
+        FeeRefund(T::AccountId, BalanceOf<T>),
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        TooManyTiers,
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    pub struct Pallet<T>(_);
+
+    /// The number of kitties with a deposit each account holds, kept up to date by the kitty hooks.
+    /// Free starter kitties don't count, or new accounts could be farmed into a discount.
+    #[pallet::storage]
+    #[pallet::getter(fn holdings)]
+    pub type Holdings<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+    /// The kitties counted in their owner's `Holdings`. A kitty without a deposit starts to count
+    /// once a buyer reserves one for it.
+    #[pallet::storage]
+    pub type CountedKitties<T: Config> = StorageMap<_, Blake2_128Concat, KittyIdOf<T>, ()>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn storage_version)]
+    pub(super) type StorageVersion<T: Config> = StorageValue<_, Releases, ValueQuery>;
+
+    /// The discount curve, sorted by `min_kitties`.
+    #[pallet::storage]
+    #[pallet::getter(fn tiers)]
+    pub type Tiers<T: Config> = StorageValue<_, Vec<DiscountTier>, ValueQuery>;
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_runtime_upgrade() -> Weight {
+            let mut weight = T::DbWeight::get().reads(1);
+            if Self::storage_version() == Releases::V1 {
+                weight = weight.saturating_add(crate::migrations::v2::migrate::<T>());
+            }
+            weight
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<(), &'static str> {
+            match Self::storage_version() {
+                Releases::V1 => crate::migrations::v2::pre_migrate::<T>(),
+                Releases::V2 => Ok(()),
+            }
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade() -> Result<(), &'static str> {
+            match Self::storage_version() {
+                // Skipped until the kitties pallet has migrated.
+                Releases::V1 => Ok(()),
+                Releases::V2 => crate::migrations::v2::post_migrate::<T>(),
+            }
+        }
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        //设置折扣档位
+        #[pallet::weight(0)]
+        pub fn set_tiers(origin: OriginFor<T>, mut tiers: Vec<DiscountTier>) -> DispatchResult {
+            T::DiscountOrigin::ensure_origin(origin)?;
+
+            ensure!(tiers.len() <= T::MaxTiers::get() as usize, Error::<T>::TooManyTiers);
+            tiers.sort_by_key(|tier| tier.min_kitties);
+            let count = tiers.len() as u32;
+            Tiers::<T>::put(tiers);
+
+            Self::deposit_event(Event::TiersSet(count));
+
+            Ok(())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// The highest tier `who` qualifies for; accounts without kitties get none.
+        pub fn tier_of(who: &T::AccountId) -> Option<DiscountTier> {
+            let held = Self::holdings(who);
+            if held == 0 {
+                return None;
+            }
+            Self::tiers().into_iter().rev().find(|tier| tier.min_kitties <= held)
+        }
+
+        fn add_holding(who: &T::AccountId) {
+            Holdings::<T>::mutate(who, |held| *held = held.saturating_add(1));
+        }
+
+        fn remove_holding(who: &T::AccountId) {
+            Holdings::<T>::mutate_exists(who, |held| {
+                *held = held.map(|held| held.saturating_sub(1)).filter(|held| *held > 0);
+            });
+        }
+
+        /// Count `kitty_id` for `owner` if a deposit is held for it.
+        fn count_kitty(owner: &T::AccountId, kitty_id: &KittyIdOf<T>) {
+            let deposit = pallet_kitties::Kitties::<T>::get(kitty_id).map(|kitty| kitty.deposit);
+            if deposit.map_or(false, |deposit| !deposit.is_zero()) {
+                CountedKitties::<T>::insert(kitty_id, ());
+                Self::add_holding(owner);
+            }
+        }
+
+        /// Stop counting `kitty_id` for `owner`, if it was counted.
+        fn uncount_kitty(owner: &T::AccountId, kitty_id: &KittyIdOf<T>) {
+            if CountedKitties::<T>::take(kitty_id).is_some() {
+                Self::remove_holding(owner);
+            }
+        }
+    }
+
+    impl<T: Config> OnKittyCreated<T::AccountId, KittyIdOf<T>> for Pallet<T> {
+        fn on_kitty_created(owner: &T::AccountId, kitty_id: &KittyIdOf<T>) {
+            Self::count_kitty(owner, kitty_id);
+        }
+    }
+
+    impl<T: Config> OnKittyTransferred<T::AccountId, KittyIdOf<T>> for Pallet<T> {
+        fn on_kitty_transferred(from: &T::AccountId, to: &T::AccountId, kitty_id: &KittyIdOf<T>) {
+            Self::uncount_kitty(from, kitty_id);
+            Self::count_kitty(to, kitty_id);
+        }
+    }
+
+    impl<T: Config> OnKittyBurned<T::AccountId, KittyIdOf<T>> for Pallet<T> {
+        fn on_kitty_burned(owner: &T::AccountId, kitty_id: &KittyIdOf<T>) {
+            Self::uncount_kitty(owner, kitty_id);
+        }
+    }
+
+    /// Raises the priority of transactions sent by kitty holders and refunds part of their fee,
+    /// according to the tier they qualify for.
+    ///
+    /// Goes next to `ChargeTransactionPayment` in the runtime's `SignedExtra`; the refund is
+    /// computed from the fee that extension charged, tip excluded.
+    ///
+    /// The refund is minted with `deposit_into_existing`, which only keeps total issuance right if
+    /// the runtime burns transaction fees, as `CurrencyAdapter<Balances, ()>` does. A runtime that
+    /// pays fees to block authors or a treasury would mint the refunded part a second time.
+    #[derive(Encode, Decode, Clone, Eq, PartialEq)]
+    pub struct KittyHolderDiscount<T: Config + Send + Sync>(PhantomData<T>);
+
+    impl<T: Config + Send + Sync> KittyHolderDiscount<T> {
+        pub fn new() -> Self {
+            Self(PhantomData)
+        }
+    }
+
+    impl<T: Config + Send + Sync> Default for KittyHolderDiscount<T> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<T: Config + Send + Sync> fmt::Debug for KittyHolderDiscount<T> {
+        #[cfg(feature = "std")]
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "KittyHolderDiscount")
+        }
+
+        #[cfg(not(feature = "std"))]
+        fn fmt(&self, _: &mut fmt::Formatter) -> fmt::Result {
+            Ok(())
+        }
+    }
+
+    impl<T: Config + Send + Sync> SignedExtension for KittyHolderDiscount<T>
+    where
+        <T as frame_system::Config>::Call: Dispatchable<Info = DispatchInfo, PostInfo = PostDispatchInfo>,
+    {
+        const IDENTIFIER: &'static str = "KittyHolderDiscount";
+        type AccountId = T::AccountId;
+        type Call = <T as frame_system::Config>::Call;
+        type AdditionalSigned = ();
+        type Pre = Option<(T::AccountId, Permill)>;
+
+        fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+            Ok(())
+        }
+
+        fn validate(
+            &self,
+            who: &Self::AccountId,
+            _call: &Self::Call,
+            _info: &DispatchInfoOf<Self::Call>,
+            _len: usize,
+        ) -> TransactionValidity {
+            let priority = Pallet::<T>::tier_of(who).map_or(0, |tier| tier.priority);
+            Ok(ValidTransaction { priority, ..Default::default() })
+        }
+
+        fn pre_dispatch(
+            self,
+            who: &Self::AccountId,
+            _call: &Self::Call,
+            _info: &DispatchInfoOf<Self::Call>,
+            _len: usize,
+        ) -> Result<Self::Pre, TransactionValidityError> {
+            Ok(Pallet::<T>::tier_of(who).map(|tier| (who.clone(), tier.discount)))
+        }
+
+        fn post_dispatch(
+            pre: Self::Pre,
+            info: &DispatchInfoOf<Self::Call>,
+            post_info: &PostDispatchInfoOf<Self::Call>,
+            len: usize,
+            _result: &DispatchResult,
+        ) -> Result<(), TransactionValidityError> {
+            if let Some((who, discount)) = pre {
+                let fee = pallet_transaction_payment::Pallet::<T>::compute_actual_fee(
+                    len as u32,
+                    info,
+                    post_info,
+                    Zero::zero(),
+                );
+                let refund: BalanceOf<T> = discount.mul_floor(fee).saturated_into::<u128>().saturated_into();
+                if !refund.is_zero()
+                    && <T as pallet_kitties::Config>::Currency::deposit_into_existing(&who, refund).is_ok()
+                {
+                    Pallet::<T>::deposit_event(Event::FeeRefund(who, refund));
+                }
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Storage migrations for the discounts pallet.
+
+pub mod v2 {
+    //! Count every kitty with a deposit in `Holdings`, not only those created or moved since the
+    //! pallet was added, by rebuilding it and `CountedKitties` from the kitties pallet.
+    //!
+    //! Needs the kitties pallet at its `V2` layout. If the kitties pallet hasn't migrated yet, this
+    //! is skipped and left for the next runtime upgrade.
+
+    use crate::{Config, CountedKitties, Holdings, Pallet, Releases, StorageVersion};
+    use frame_support::{
+        storage::migration::remove_storage_prefix,
+        traits::{Get, PalletInfoAccess},
+        weights::Weight,
+    };
+    use pallet_kitties::{Kitties, KittyIdOf};
+    use sp_runtime::traits::Zero;
+    use sp_std::{collections::btree_map::BTreeMap, prelude::*};
+
+    fn kitties_migrated<T: Config>() -> bool {
+        pallet_kitties::Pallet::<T>::storage_version() == pallet_kitties::Releases::V2
+    }
+
+    /// The kitties with a deposit, how many of them each account holds, and the number of kitties read.
+    fn count_holdings<T: Config>() -> (Vec<KittyIdOf<T>>, BTreeMap<T::AccountId, u32>, u64) {
+        let mut counted = Vec::new();
+        let mut holdings = BTreeMap::new();
+        let mut read = 0;
+        for (kitty_id, kitty) in Kitties::<T>::iter() {
+            read += 1;
+            if !kitty.deposit.is_zero() {
+                counted.push(kitty_id);
+                *holdings.entry(kitty.owner).or_default() += 1;
+            }
+        }
+        (counted, holdings, read)
+    }
+
+    pub fn pre_migrate<T: Config>() -> Result<(), &'static str> {
+        frame_support::ensure!(StorageVersion::<T>::get() == Releases::V1, "discounts storage is not at V1");
+
+        Ok(())
+    }
+
+    pub fn migrate<T: Config>() -> Weight {
+        if !kitties_migrated::<T>() {
+            sp_runtime::print("discounts: kitties storage is not at V2, skipping the holdings backfill");
+            return T::DbWeight::get().reads(1);
+        }
+
+        let (counted, holdings, read) = count_holdings::<T>();
+        let prefix = <Pallet<T> as PalletInfoAccess>::name().as_bytes();
+        remove_storage_prefix(prefix, b"Holdings", &[]);
+        remove_storage_prefix(prefix, b"CountedKitties", &[]);
+        for kitty_id in counted.iter() {
+            CountedKitties::<T>::insert(kitty_id, ());
+        }
+        for (owner, held) in holdings.iter() {
+            Holdings::<T>::insert(owner, held);
+        }
+
+        StorageVersion::<T>::put(Releases::V2);
+
+        T::DbWeight::get().reads_writes(read + 1, (counted.len() + holdings.len()) as u64 + 3)
+    }
+
+    /// Check every account's holdings match the kitties with a deposit it owns.
+    pub fn post_migrate<T: Config>() -> Result<(), &'static str> {
+        frame_support::ensure!(StorageVersion::<T>::get() == Releases::V2, "discounts storage is not at V2");
+
+        let (counted, holdings, _) = count_holdings::<T>();
+        for (owner, held) in Holdings::<T>::iter() {
+            frame_support::ensure!(holdings.get(&owner) == Some(&held), "holdings do not match owned kitties");
+        }
+        frame_support::ensure!(Holdings::<T>::iter().count() == holdings.len(), "holders missing from Holdings");
+        frame_support::ensure!(
+            counted.iter().all(|kitty_id| CountedKitties::<T>::contains_key(kitty_id))
+                && CountedKitties::<T>::iter().count() == counted.len(),
+            "CountedKitties do not match kitties with a deposit"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,30 @@
+use crate as pallet_discounts;
+use frame_support::{parameter_types, weights::IdentityFee};
+use pallet_transaction_payment::CurrencyAdapter;
+
+pallet_kitties::kitty_test_runtime! {
+	hooks = DiscountModule, balance = 1 << 40;
+	TransactionPayment: pallet_transaction_payment::{Pallet, Storage},
+	DiscountModule: pallet_discounts::{Pallet, Call, Storage, Event<T>},
+}
+
+parameter_types! {
+	pub const TransactionByteFee: Balance = 1;
+}
+
+impl pallet_transaction_payment::Config for Test {
+	type OnChargeTransaction = CurrencyAdapter<Balances, ()>;
+	type TransactionByteFee = TransactionByteFee;
+	type WeightToFee = IdentityFee<Balance>;
+	type FeeMultiplierUpdate = ();
+}
+
+parameter_types! {
+	pub const MaxTiers: u32 = 3;
+}
+
+impl pallet_discounts::Config for Test {
+	type Event = Event;
+	type DiscountOrigin = frame_system::EnsureRoot<u64>;
+	type MaxTiers = MaxTiers;
+}
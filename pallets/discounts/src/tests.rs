@@ -0,0 +1,157 @@
+use crate::{Error, Event, DiscountTier, KittyHolderDiscount, mock::*};
+use super::*;
+use frame_support::{
+	assert_ok, assert_noop,
+	storage::migration::put_storage_value,
+	traits::Hooks,
+	weights::{DispatchInfo, PostDispatchInfo},
+};
+use pallet_transaction_payment::ChargeTransactionPayment;
+use sp_runtime::{Permill, testing::TestSignature, traits::{BadOrigin, SignedExtension}};
+
+const CALL: &<Test as frame_system::Config>::Call = &Call::Balances(pallet_balances::Call::transfer(2, 69));
+
+fn set_tiers() {
+	assert_ok!(DiscountModule::set_tiers(Origin::root(), vec![
+		DiscountTier { min_kitties: 2, discount: Permill::from_percent(50), priority: 1_000 },
+		DiscountTier { min_kitties: 1, discount: Permill::from_percent(10), priority: 100 },
+	]));
+}
+
+#[test]
+fn holdings_follow_kitties() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1, 1]);
+		assert_eq!(DiscountModule::holdings(1), 2);
+
+		assert_ok!(KittyModule::transfer(Origin::signed(1), 2, (0, 0)));
+		assert_ok!(KittyModule::sell_kitty(Origin::signed(1), (0, 1), Some(100)));
+		assert_ok!(KittyModule::buy_kitty(Origin::signed(2), (0, 1)));
+		assert_eq!(DiscountModule::holdings(1), 0);
+		assert_eq!(DiscountModule::holdings(2), 2);
+	});
+}
+
+#[test]
+fn starter_kitties_are_not_counted() {
+	new_test_ext().execute_with(|| {
+		let signature = TestSignature(5, KittyModule::starter_claim_message(&5));
+		assert_ok!(KittyModule::claim_starter_kitty(Origin::none(), 5, signature));
+		assert_eq!(DiscountModule::holdings(5), 0);
+
+		//转让后仍不计入，被买下时买方交了押金才开始计入
+		assert_ok!(KittyModule::transfer(Origin::signed(5), 2, (0, 0)));
+		assert_eq!(DiscountModule::holdings(2), 0);
+		assert_ok!(KittyModule::sell_kitty(Origin::signed(2), (0, 0), Some(100)));
+		assert_ok!(KittyModule::buy_kitty(Origin::signed(1), (0, 0)));
+		assert_eq!((DiscountModule::holdings(1), DiscountModule::holdings(2)), (1, 0));
+
+		assert_ok!(KittyModule::burn(Origin::signed(1), (0, 0)));
+		assert_eq!(DiscountModule::holdings(1), 0);
+	});
+}
+
+#[test]
+fn set_tiers_works() {
+	new_test_ext().execute_with(|| {
+		set_tiers();
+		System::assert_last_event(mock::Event::DiscountModule(Event::TiersSet(2)));
+		assert_eq!(DiscountModule::tiers()[0].min_kitties, 1);
+	});
+}
+
+#[test]
+fn set_tiers_failed() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(DiscountModule::set_tiers(Origin::signed(1), vec![]), BadOrigin);
+		let tier = DiscountTier { min_kitties: 1, discount: Permill::zero(), priority: 0 };
+		assert_noop!(
+			DiscountModule::set_tiers(Origin::root(), vec![tier; 4]),
+			Error::<Test>::TooManyTiers
+		);
+	});
+}
+
+#[test]
+fn holders_get_priority() {
+	new_test_ext().execute_with(|| {
+		set_tiers();
+		let info = DispatchInfo::default();
+		let priority = |who| KittyHolderDiscount::<Test>::new().validate(&who, CALL, &info, 10).unwrap().priority;
+		assert_eq!(priority(1), 0);
+
+		create_kitties(&[1]);
+		assert_eq!(priority(1), 100);
+		create_kitties(&[1]);
+		assert_eq!(priority(1), 1_000);
+	});
+}
+
+#[test]
+fn holders_get_fee_refund() {
+	new_test_ext().execute_with(|| {
+		set_tiers();
+		create_kitties(&[1, 1]);
+
+		let info = DispatchInfo { weight: 1_000, ..Default::default() };
+		let post_info = PostDispatchInfo::default();
+		let len = 10;
+		let before = Balances::free_balance(1);
+
+		let fee_pre = ChargeTransactionPayment::<Test>::from(0).pre_dispatch(&1, CALL, &info, len).unwrap();
+		let pre = KittyHolderDiscount::<Test>::new().pre_dispatch(&1, CALL, &info, len).unwrap();
+		assert_ok!(ChargeTransactionPayment::<Test>::post_dispatch(fee_pre, &info, &post_info, len, &Ok(())));
+		assert_ok!(KittyHolderDiscount::<Test>::post_dispatch(pre, &info, &post_info, len, &Ok(())));
+
+		let fee = TransactionPayment::compute_actual_fee(len as u32, &info, &post_info, 0);
+		assert_eq!(Balances::free_balance(1), before - fee + fee / 2);
+		System::assert_last_event(mock::Event::DiscountModule(Event::FeeRefund(1, fee / 2)));
+	});
+}
+
+#[test]
+fn non_holders_pay_full_fee() {
+	new_test_ext().execute_with(|| {
+		set_tiers();
+		let info = DispatchInfo { weight: 1_000, ..Default::default() };
+		let pre = KittyHolderDiscount::<Test>::new().pre_dispatch(&1, CALL, &info, 10).unwrap();
+		assert_eq!(pre, None);
+	});
+}
+
+#[test]
+fn migrate_backfills_holdings() {
+	new_test_ext().execute_with(|| {
+		//kitty模块已是最新存储格式
+		put_storage_value(b"KittyModule", b"StorageVersion", &[], pallet_kitties::Releases::V2);
+		create_kitties(&[1, 1, 2]);
+		let signature = TestSignature(5, KittyModule::starter_claim_message(&5));
+		assert_ok!(KittyModule::claim_starter_kitty(Origin::none(), 5, signature));
+		//模拟折扣模块上线之前就存在的kitty
+		Holdings::<Test>::remove(1);
+		CountedKitties::<Test>::remove((0, 0));
+		Holdings::<Test>::insert(2, 5);
+		assert_eq!(DiscountModule::storage_version(), Releases::V1);
+
+		assert_ok!(migrations::v2::pre_migrate::<Test>());
+		DiscountModule::on_runtime_upgrade();
+		assert_ok!(migrations::v2::post_migrate::<Test>());
+
+		assert_eq!(DiscountModule::storage_version(), Releases::V2);
+		assert_eq!((DiscountModule::holdings(1), DiscountModule::holdings(2)), (2, 1));
+		//免费领取的kitty不计入
+		assert_eq!(DiscountModule::holdings(5), 0);
+	});
+}
+
+#[test]
+fn migrate_waits_for_kitties() {
+	new_test_ext().execute_with(|| {
+		put_storage_value(b"KittyModule", b"StorageVersion", &[], pallet_kitties::Releases::V1);
+		Holdings::<Test>::insert(2, 5);
+
+		DiscountModule::on_runtime_upgrade();
+		assert_eq!(DiscountModule::storage_version(), Releases::V1);
+		assert_eq!(DiscountModule::holdings(2), 5);
+	});
+}
@@ -0,0 +1,216 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+    use frame_support::{pallet_prelude::*, transactional, traits::{BalanceStatus, Currency, ReservableCurrency}};
+    use frame_system::pallet_prelude::*;
+    use sp_runtime::{RuntimeDebug, helpers_128bit::multiply_by_rational, traits::{SaturatedConversion, Saturating, Zero}};
+    use sp_std::prelude::*;
+    use codec::{Encode, Decode};
+    use pallet_kitties::{BalanceOf, KittyIdOf};
+
+    /// A kitty locked in a vault and split into fungible shares.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+    pub struct Vault<AccountId, Balance> {
+        /// The owner who fractionalised the kitty; it stays the kitty's owner while vaulted.
+        pub curator: AccountId,
+        pub total_shares: u128,
+        /// The price at which anyone can buy the kitty out of the vault.
+        pub reserve_price: Balance,
+    }
+
+    pub type VaultOf<T> = Vault<<T as frame_system::Config>::AccountId, BalanceOf<T>>;
+
+    #[pallet::config]
+    pub trait Config: pallet_kitties::Config {
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+    }
+
+    #[pallet::event]
+    #[pallet::metadata(T::AccountId = "AccountId")]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// [curator, kitty_id, total_shares, reserve_price]
+        Fractionalize(T::AccountId, KittyIdOf<T>, u128, BalanceOf<T>),
+        /// [from, to, kitty_id, amount]
+        SharesTransfer(T::AccountId, T::AccountId, KittyIdOf<T>, u128),
+        /// [buyer, kitty_id, reserve_price]
+        Buyout(T::AccountId, KittyIdOf<T>, BalanceOf<T>),
+        /// A holder of every share took the kitty back out. [who, kitty_id]
+        Redeem(T::AccountId, KittyIdOf<T>),
+        /// A buyout payout could not be paid out and is held for the holder to claim. [holder, kitty_id, amount]
+        PayoutPending(T::AccountId, KittyIdOf<T>, BalanceOf<T>),
+        /// [holder, kitty_id, amount]
+        PayoutClaim(T::AccountId, KittyIdOf<T>, BalanceOf<T>),
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        NotOwner,
+        InvalidVault,
+        ZeroShares,
+        InsufficientShares,
+        NotAllShares,
+        BalanceLitter,
+        NoPayout,
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    pub struct Pallet<T>(_);
+
+    #[pallet::storage]
+    #[pallet::getter(fn vaults)]
+    pub type Vaults<T: Config> = StorageMap<_, Blake2_128Concat, KittyIdOf<T>, VaultOf<T>>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn shares)]
+    pub type Shares<T: Config> = StorageDoubleMap<_, Blake2_128Concat, KittyIdOf<T>, Blake2_128Concat, T::AccountId, u128, ValueQuery>;
+
+    /// Buyout payouts still reserved on the buyer's account: (buyer, amount).
+    #[pallet::storage]
+    #[pallet::getter(fn pending_payouts)]
+    pub type PendingPayouts<T: Config> =
+        StorageDoubleMap<_, Blake2_128Concat, KittyIdOf<T>, Blake2_128Concat, T::AccountId, (T::AccountId, BalanceOf<T>)>;
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        //把kitty锁进金库并发行份额
+        #[pallet::weight(0)]
+        pub fn fractionalize(
+            origin: OriginFor<T>,
+            kitty_id: KittyIdOf<T>,
+            total_shares: u128,
+            reserve_price: BalanceOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(Some(who.clone()) == pallet_kitties::Pallet::<T>::owner(kitty_id), Error::<T>::NotOwner);
+            ensure!(total_shares > 0, Error::<T>::ZeroShares);
+            pallet_kitties::Pallet::<T>::lock(kitty_id)?;
+
+            Vaults::<T>::insert(kitty_id, Vault { curator: who.clone(), total_shares, reserve_price });
+            Shares::<T>::insert(kitty_id, &who, total_shares);
+
+            Self::deposit_event(Event::Fractionalize(who, kitty_id, total_shares, reserve_price));
+
+            Ok(())
+        }
+
+        //转让份额
+        #[pallet::weight(0)]
+        pub fn transfer_shares(
+            origin: OriginFor<T>,
+            kitty_id: KittyIdOf<T>,
+            to: T::AccountId,
+            amount: u128,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(Vaults::<T>::contains_key(kitty_id), Error::<T>::InvalidVault);
+            ensure!(amount > 0, Error::<T>::ZeroShares);
+            Shares::<T>::try_mutate_exists(kitty_id, &who, |balance| -> DispatchResult {
+                let remaining = balance.unwrap_or(0).checked_sub(amount).ok_or(Error::<T>::InsufficientShares)?;
+                *balance = Some(remaining).filter(|r| *r > 0);
+                Ok(())
+            })?;
+            Shares::<T>::mutate(kitty_id, &to, |balance| *balance += amount);
+
+            Self::deposit_event(Event::SharesTransfer(who, to, kitty_id, amount));
+
+            Ok(())
+        }
+
+        //按保留价买断，货款按份额比例分给所有持有人，无法到账的部分留给持有人自行领取
+        #[pallet::weight(0)]
+        #[transactional]
+        pub fn buyout(origin: OriginFor<T>, kitty_id: KittyIdOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let vault = Vaults::<T>::take(kitty_id).ok_or(Error::<T>::InvalidVault)?;
+            let reserve_price: u128 = vault.reserve_price.saturated_into();
+            let payouts: Vec<(T::AccountId, BalanceOf<T>)> = Shares::<T>::drain_prefix(kitty_id)
+                .filter(|(holder, _)| *holder != who)
+                .map(|(holder, amount)| {
+                    let payout = multiply_by_rational(reserve_price, amount, vault.total_shares).unwrap_or(0);
+                    (holder, payout.saturated_into())
+                })
+                .collect();
+
+            // The whole price is reserved up front, so a holder that cannot be paid now
+            // (e.g. a reaped account) only delays its own payout.
+            let total = payouts.iter().fold(BalanceOf::<T>::zero(), |total, (_, payout)| total.saturating_add(*payout));
+            <T as pallet_kitties::Config>::Currency::reserve(&who, total).map_err(|_| Error::<T>::BalanceLitter)?;
+            for (holder, payout) in payouts {
+                let paid = <T as pallet_kitties::Config>::Currency::repatriate_reserved(
+                    &who,
+                    &holder,
+                    payout,
+                    BalanceStatus::Free,
+                );
+                if paid.is_err() {
+                    PendingPayouts::<T>::insert(kitty_id, &holder, (who.clone(), payout));
+                    Self::deposit_event(Event::PayoutPending(holder, kitty_id, payout));
+                }
+            }
+            Self::release(kitty_id, &vault.curator, &who)?;
+
+            Self::deposit_event(Event::Buyout(who, kitty_id, vault.reserve_price));
+
+            Ok(())
+        }
+
+        //持有全部份额时取回kitty
+        #[pallet::weight(0)]
+        pub fn redeem(origin: OriginFor<T>, kitty_id: KittyIdOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let vault = Self::vaults(kitty_id).ok_or(Error::<T>::InvalidVault)?;
+            ensure!(Self::shares(kitty_id, &who) == vault.total_shares, Error::<T>::NotAllShares);
+
+            Vaults::<T>::remove(kitty_id);
+            Shares::<T>::remove(kitty_id, &who);
+            Self::release(kitty_id, &vault.curator, &who)?;
+
+            Self::deposit_event(Event::Redeem(who, kitty_id));
+
+            Ok(())
+        }
+
+        //领取买断时未能到账的货款
+        #[pallet::weight(0)]
+        pub fn claim_payout(origin: OriginFor<T>, kitty_id: KittyIdOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let (buyer, payout) = PendingPayouts::<T>::get(kitty_id, &who).ok_or(Error::<T>::NoPayout)?;
+            let leftover = <T as pallet_kitties::Config>::Currency::repatriate_reserved(
+                &buyer,
+                &who,
+                payout,
+                BalanceStatus::Free,
+            )?;
+            PendingPayouts::<T>::remove(kitty_id, &who);
+
+            Self::deposit_event(Event::PayoutClaim(who, kitty_id, payout.saturating_sub(leftover)));
+
+            Ok(())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Unlock a vaulted kitty and hand it from the curator to its new owner.
+        fn release(kitty_id: KittyIdOf<T>, curator: &T::AccountId, to: &T::AccountId) -> DispatchResult {
+            pallet_kitties::Pallet::<T>::unlock(kitty_id);
+            if curator != to {
+                pallet_kitties::Pallet::<T>::do_transfer(curator, to, kitty_id)?;
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,9 @@
+use crate as pallet_fractional;
+
+pallet_kitties::kitty_test_runtime! {
+	FractionalModule: pallet_fractional::{Pallet, Call, Storage, Event<T>},
+}
+
+impl pallet_fractional::Config for Test {
+	type Event = Event;
+}
@@ -0,0 +1,93 @@
+use crate::{Error, Event, mock::*};
+use frame_support::{assert_ok, assert_noop, traits::Currency};
+use super::*;
+
+#[test]
+fn fractionalize_works() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1]);
+		assert_noop!(FractionalModule::fractionalize(Origin::signed(2), (0, 0), 100, 10_000), Error::<Test>::NotOwner);
+		assert_noop!(FractionalModule::fractionalize(Origin::signed(1), (0, 0), 0, 10_000), Error::<Test>::ZeroShares);
+		assert_ok!(FractionalModule::fractionalize(Origin::signed(1), (0, 0), 100, 10_000));
+		System::assert_has_event(mock::Event::FractionalModule(Event::Fractionalize(1, (0, 0), 100, 10_000)));
+
+		assert_eq!(FractionalModule::shares((0, 0), 1), 100);
+		assert!(KittyModule::is_locked((0, 0)));
+		assert_noop!(
+			FractionalModule::fractionalize(Origin::signed(1), (0, 0), 100, 10_000),
+			pallet_kitties::Error::<Test>::KittyLocked
+		);
+	})
+}
+
+#[test]
+fn transfer_shares_works() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1]);
+		assert_noop!(FractionalModule::transfer_shares(Origin::signed(1), (0, 0), 2, 10), Error::<Test>::InvalidVault);
+		assert_ok!(FractionalModule::fractionalize(Origin::signed(1), (0, 0), 100, 10_000));
+		assert_noop!(FractionalModule::transfer_shares(Origin::signed(1), (0, 0), 2, 101), Error::<Test>::InsufficientShares);
+		assert_ok!(FractionalModule::transfer_shares(Origin::signed(1), (0, 0), 2, 30));
+		assert_eq!(FractionalModule::shares((0, 0), 1), 70);
+		assert_eq!(FractionalModule::shares((0, 0), 2), 30);
+	})
+}
+
+#[test]
+fn buyout_pays_holders_pro_rata() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1]);
+		assert_ok!(FractionalModule::fractionalize(Origin::signed(1), (0, 0), 100, 10_000));
+		assert_ok!(FractionalModule::transfer_shares(Origin::signed(1), (0, 0), 2, 25));
+		assert_ok!(FractionalModule::transfer_shares(Origin::signed(1), (0, 0), 0, 15));
+
+		assert_ok!(FractionalModule::buyout(Origin::signed(0), (0, 0)));
+		System::assert_has_event(mock::Event::FractionalModule(Event::Buyout(0, (0, 0), 10_000)));
+		assert_eq!(Balances::free_balance(1), 100_000_000 - 1_000 + 6_000);
+		assert_eq!(Balances::free_balance(2), 100_000_000 + 2_500);
+		assert_eq!(Balances::free_balance(0), 100_000_000 - 8_500);
+
+		assert_eq!(KittyModule::owner((0, 0)), Some(0));
+		assert!(!KittyModule::is_locked((0, 0)));
+		assert!(FractionalModule::vaults((0, 0)).is_none());
+		assert_eq!(FractionalModule::shares((0, 0), 2), 0);
+	})
+}
+
+#[test]
+fn buyout_holds_payout_for_dead_holder() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1]);
+		assert_ok!(FractionalModule::fractionalize(Origin::signed(1), (0, 0), 100, 10_000));
+		//账户3不存在，无法直接收款
+		assert_ok!(FractionalModule::transfer_shares(Origin::signed(1), (0, 0), 3, 40));
+
+		assert_ok!(FractionalModule::buyout(Origin::signed(0), (0, 0)));
+		System::assert_has_event(mock::Event::FractionalModule(Event::PayoutPending(3, (0, 0), 4_000)));
+		assert_eq!(KittyModule::owner((0, 0)), Some(0));
+		assert_eq!(Balances::free_balance(1), 100_000_000 - 1_000 + 6_000);
+		assert_eq!(Balances::free_balance(0), 100_000_000 - 10_000);
+		assert_eq!(Balances::reserved_balance(0), 4_000);
+
+		assert_noop!(FractionalModule::claim_payout(Origin::signed(2), (0, 0)), Error::<Test>::NoPayout);
+		Balances::make_free_balance_be(&3, 1_000);
+		assert_ok!(FractionalModule::claim_payout(Origin::signed(3), (0, 0)));
+		System::assert_has_event(mock::Event::FractionalModule(Event::PayoutClaim(3, (0, 0), 4_000)));
+		assert_eq!(Balances::free_balance(3), 5_000);
+		assert_eq!(Balances::reserved_balance(0), 0);
+		assert!(FractionalModule::pending_payouts((0, 0), 3).is_none());
+	})
+}
+
+#[test]
+fn redeem_works() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1]);
+		assert_ok!(FractionalModule::fractionalize(Origin::signed(1), (0, 0), 100, 10_000));
+		assert_ok!(FractionalModule::transfer_shares(Origin::signed(1), (0, 0), 2, 100));
+		assert_noop!(FractionalModule::redeem(Origin::signed(1), (0, 0)), Error::<Test>::NotAllShares);
+		assert_ok!(FractionalModule::redeem(Origin::signed(2), (0, 0)));
+		assert_eq!(KittyModule::owner((0, 0)), Some(2));
+		assert!(!KittyModule::is_locked((0, 0)));
+	})
+}
@@ -0,0 +1,28 @@
+//! Runtime API definition for the kitties pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+    #[api_version(4)]
+    pub trait KittiesApi<KittyId, AccountId, KittyInfo, Phenotype, HistoryEntry> where
+        KittyId: Codec,
+        AccountId: Codec,
+        KittyInfo: Codec,
+        Phenotype: Codec,
+        HistoryEntry: Codec,
+    {
+        /// A single kitty, if it exists.
+        fn kitty(kitty_id: KittyId) -> Option<KittyInfo>;
+        /// Up to `limit` kitties held by `owner` with an id above `start_after`, ordered by id.
+        fn kitties_of(owner: AccountId, start_after: Option<KittyId>, limit: u32) -> Vec<(KittyId, KittyInfo)>;
+        /// Up to `limit` kitties listed for a native price with an id above `start_after`, ordered by id.
+        fn listings(start_after: Option<KittyId>, limit: u32) -> Vec<(KittyId, KittyInfo)>;
+        /// The traits expressed by a kitty's DNA.
+        fn phenotype(kitty_id: KittyId) -> Option<Phenotype>;
+        /// The recorded transfers and sales of a kitty, oldest first.
+        fn kitty_history(kitty_id: KittyId) -> Vec<HistoryEntry>;
+    }
+}
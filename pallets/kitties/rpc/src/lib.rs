@@ -0,0 +1,229 @@
+//! JSON-RPC methods for querying kitties without stitching storage maps together on the client.
+
+use std::sync::Arc;
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use pallet_kitties::{HistoryEntry, KittyInfo, SalePrice};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_rpc::number::NumberOrHex;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+pub use pallet_kitties_rpc_runtime_api::KittiesApi as KittiesRuntimeApi;
+
+pub mod svg;
+
+/// A kitty with its price as a `NumberOrHex`, which JavaScript clients can read without losing precision.
+pub type RpcKittyInfo<AccountId> = KittyInfo<AccountId, NumberOrHex>;
+/// A history entry with its price as `NumberOrHex`, like `RpcKittyInfo`.
+pub type RpcHistoryEntry<BlockNumber, AccountId, AssetId> =
+	HistoryEntry<BlockNumber, AccountId, SalePrice<NumberOrHex, AssetId, NumberOrHex>>;
+
+/// The page size used when the caller does not give one.
+const DEFAULT_PAGE_SIZE: u32 = 20;
+/// The largest page a caller can ask for.
+const MAX_PAGE_SIZE: u32 = 100;
+
+#[rpc]
+pub trait KittiesApi<BlockHash, KittyId, AccountId, BlockNumber, Balance, AssetId, AssetBalance> {
+	/// A single kitty, or `null` if it does not exist.
+	#[rpc(name = "kitties_getKitty")]
+	fn get_kitty(&self, kitty_id: KittyId, at: Option<BlockHash>) -> Result<Option<RpcKittyInfo<AccountId>>>;
+
+	/// Up to `limit` kitties held by `owner` with an id above `start_after`, ordered by id.
+	/// Pass the last id of a page as `start_after` to get the next one.
+	#[rpc(name = "kitties_listByOwner")]
+	fn list_by_owner(
+		&self,
+		owner: AccountId,
+		start_after: Option<KittyId>,
+		limit: Option<u32>,
+		at: Option<BlockHash>,
+	) -> Result<Vec<(KittyId, RpcKittyInfo<AccountId>)>>;
+
+	/// Up to `limit` kitties listed for a native price with an id above `start_after`, ordered by id.
+	#[rpc(name = "kitties_listForSale")]
+	fn list_for_sale(
+		&self,
+		start_after: Option<KittyId>,
+		limit: Option<u32>,
+		at: Option<BlockHash>,
+	) -> Result<Vec<(KittyId, RpcKittyInfo<AccountId>)>>;
+
+	/// The traits expressed by a kitty's DNA.
+	#[rpc(name = "kitties_getPhenotype")]
+	fn get_phenotype(&self, kitty_id: KittyId, at: Option<BlockHash>) -> Result<Option<pallet_kitties::Phenotype>>;
+
+	/// The recorded transfers and sales of a kitty, oldest first.
+	#[rpc(name = "kitties_getHistory")]
+	fn get_history(
+		&self,
+		kitty_id: KittyId,
+		at: Option<BlockHash>,
+	) -> Result<Vec<RpcHistoryEntry<BlockNumber, AccountId, AssetId>>>;
+
+	/// An SVG image of a kitty, or `null` if it does not exist.
+	#[rpc(name = "kitties_renderSvg")]
+	fn render_svg(&self, kitty_id: KittyId, at: Option<BlockHash>) -> Result<Option<String>>;
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+	/// The call to runtime failed.
+	RuntimeError,
+}
+
+impl From<Error> for i64 {
+	fn from(e: Error) -> i64 {
+		match e {
+			Error::RuntimeError => 1,
+		}
+	}
+}
+
+/// Implements the `KittiesApi` RPC trait by calling into the runtime.
+pub struct Kitties<C, B> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> Kitties<C, B> {
+	/// Create new `Kitties` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+/// The page size to query, with the default applied and capped.
+fn page_limit(limit: Option<u32>) -> u32 {
+	limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE)
+}
+
+fn rpc_kitty<AccountId, Balance: Into<NumberOrHex>>(kitty: KittyInfo<AccountId, Balance>) -> RpcKittyInfo<AccountId> {
+	KittyInfo {
+		dna: kitty.dna,
+		owner: kitty.owner,
+		price: kitty.price.map(Into::into),
+		depositor: kitty.depositor,
+		deposit: kitty.deposit.into(),
+	}
+}
+
+fn rpc_history_entry<BlockNumber, AccountId, Balance, AssetId, AssetBalance>(
+	entry: HistoryEntry<BlockNumber, AccountId, SalePrice<Balance, AssetId, AssetBalance>>,
+) -> RpcHistoryEntry<BlockNumber, AccountId, AssetId> where
+	Balance: Into<NumberOrHex>,
+	AssetBalance: Into<NumberOrHex>,
+{
+	HistoryEntry {
+		block_number: entry.block_number,
+		from: entry.from,
+		to: entry.to,
+		price: entry.price.map(|price| match price {
+			SalePrice::Native(amount) => SalePrice::Native(amount.into()),
+			SalePrice::Asset(asset_id, amount) => SalePrice::Asset(asset_id, amount.into()),
+		}),
+	}
+}
+
+fn runtime_error(message: &str, e: impl std::fmt::Debug) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(Error::RuntimeError.into()),
+		message: message.into(),
+		data: Some(format!("{:?}", e).into()),
+	}
+}
+
+impl<C, Block, KittyId, AccountId, BlockNumber, Balance, AssetId, AssetBalance>
+	KittiesApi<<Block as BlockT>::Hash, KittyId, AccountId, BlockNumber, Balance, AssetId, AssetBalance>
+	for Kitties<C, Block>
+where
+	Block: BlockT,
+	C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C::Api: KittiesRuntimeApi<
+		Block,
+		KittyId,
+		AccountId,
+		KittyInfo<AccountId, Balance>,
+		pallet_kitties::Phenotype,
+		HistoryEntry<BlockNumber, AccountId, SalePrice<Balance, AssetId, AssetBalance>>,
+	>,
+	KittyId: Codec,
+	AccountId: Codec,
+	BlockNumber: Codec,
+	Balance: Codec + Into<NumberOrHex>,
+	AssetId: Codec,
+	AssetBalance: Codec + Into<NumberOrHex>,
+{
+	fn get_kitty(
+		&self,
+		kitty_id: KittyId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<RpcKittyInfo<AccountId>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		let kitty = api.kitty(&at, kitty_id).map_err(|e| runtime_error("Unable to query kitty.", e))?;
+		Ok(kitty.map(rpc_kitty))
+	}
+
+	fn list_by_owner(
+		&self,
+		owner: AccountId,
+		start_after: Option<KittyId>,
+		limit: Option<u32>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<(KittyId, RpcKittyInfo<AccountId>)>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		let kitties = api.kitties_of(&at, owner, start_after, page_limit(limit))
+			.map_err(|e| runtime_error("Unable to query kitties.", e))?;
+		Ok(kitties.into_iter().map(|(kitty_id, kitty)| (kitty_id, rpc_kitty(kitty))).collect())
+	}
+
+	fn list_for_sale(
+		&self,
+		start_after: Option<KittyId>,
+		limit: Option<u32>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<(KittyId, RpcKittyInfo<AccountId>)>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		let kitties = api.listings(&at, start_after, page_limit(limit))
+			.map_err(|e| runtime_error("Unable to query listings.", e))?;
+		Ok(kitties.into_iter().map(|(kitty_id, kitty)| (kitty_id, rpc_kitty(kitty))).collect())
+	}
+
+	fn get_phenotype(
+		&self,
+		kitty_id: KittyId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<pallet_kitties::Phenotype>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.phenotype(&at, kitty_id).map_err(|e| runtime_error("Unable to query phenotype.", e))
+	}
+
+	fn get_history(
+		&self,
+		kitty_id: KittyId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<RpcHistoryEntry<BlockNumber, AccountId, AssetId>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		let history = api.kitty_history(&at, kitty_id).map_err(|e| runtime_error("Unable to query kitty history.", e))?;
+		Ok(history.into_iter().map(rpc_history_entry).collect())
+	}
+
+	fn render_svg(&self, kitty_id: KittyId, at: Option<<Block as BlockT>::Hash>) -> Result<Option<String>> {
+		let phenotype = self.get_phenotype(kitty_id, at)?;
+
+		Ok(phenotype.map(|phenotype| svg::render(&phenotype)))
+	}
+}
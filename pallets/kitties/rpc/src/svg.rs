@@ -0,0 +1,117 @@
+//! Deterministic SVG portraits of kitties, drawn from their phenotype.
+//!
+//! Follows the front-end's `KittyAvatar`, which `KittyCards` draws every kitty with: each trait
+//! picks one of four images by its gene modulo four, the body images differ only in colour, and
+//! the layers are stacked body, fur, mouth, eyes, accessory. A change to either has to be made to
+//! both, so that a kitty looks the same in the RPC's image as on its card.
+
+use pallet_kitties::Phenotype;
+
+#[cfg(test)]
+mod tests;
+
+/// Body colours, in the order of the front-end's body images: blue, green, red, yellow.
+const BODY_COLOURS: [&str; 4] = ["#4a90d9", "#5cb85c", "#d9534f", "#f0c419"];
+
+const NOSE: &str = r##"<path d="M95 120 L105 120 L100 126 Z" fill="#f49"/>"##;
+
+/// Fur patterns, drawn over the body.
+const FUR: [&[&str]; 4] = [
+	&[
+		r##"<path d="M85 45 l5 20 M100 42 v22 M115 45 l-5 20" stroke="#000" stroke-opacity="0.3" stroke-width="4"/>"##,
+	],
+	&[
+		r##"<circle cx="70" cy="140" r="8" fill="#000" fill-opacity="0.2"/>"##,
+		r##"<circle cx="130" cy="80" r="10" fill="#000" fill-opacity="0.2"/>"##,
+		r##"<circle cx="122" cy="155" r="6" fill="#000" fill-opacity="0.2"/>"##,
+	],
+	&[
+		r##"<ellipse cx="100" cy="135" rx="35" ry="30" fill="#fff" fill-opacity="0.6"/>"##,
+	],
+	&[
+		r##"<path d="M40 100 h22 M40 115 h22 M138 100 h22 M138 115 h22" stroke="#000" stroke-opacity="0.3" stroke-width="4"/>"##,
+	],
+];
+
+/// Mouths, each under the same nose.
+const MOUTHS: [&[&str]; 4] = [
+	&[
+		r##"<path d="M88 132 q12 10 24 0" stroke="#222" stroke-width="3" fill="none"/>"##,
+	],
+	&[
+		r##"<path d="M90 134 h20" stroke="#222" stroke-width="3" fill="none"/>"##,
+	],
+	&[
+		r##"<ellipse cx="100" cy="136" rx="6" ry="8" fill="#822"/>"##,
+	],
+	&[
+		r##"<path d="M88 132 q12 10 24 0" stroke="#222" stroke-width="3" fill="none"/>"##,
+		r##"<ellipse cx="100" cy="140" rx="5" ry="6" fill="#f49"/>"##,
+	],
+];
+
+/// Eyes.
+const EYES: [&[&str]; 4] = [
+	&[
+		r##"<circle cx="75" cy="100" r="10" fill="#222"/>"##,
+		r##"<circle cx="125" cy="100" r="10" fill="#222"/>"##,
+	],
+	&[
+		r##"<path d="M65 100 q10 8 20 0 M115 100 q10 8 20 0" stroke="#222" stroke-width="3" fill="none"/>"##,
+	],
+	&[
+		r##"<circle cx="75" cy="100" r="14" fill="#fff"/>"##,
+		r##"<circle cx="75" cy="102" r="7" fill="#222"/>"##,
+		r##"<circle cx="125" cy="100" r="14" fill="#fff"/>"##,
+		r##"<circle cx="125" cy="102" r="7" fill="#222"/>"##,
+	],
+	&[
+		r##"<ellipse cx="75" cy="100" rx="10" ry="5" fill="#3a3"/>"##,
+		r##"<ellipse cx="125" cy="100" rx="10" ry="5" fill="#3a3"/>"##,
+	],
+];
+
+/// Accessories: glasses, a pipe, a bow and freckles.
+const ACCESSORIES: [&[&str]; 4] = [
+	&[
+		r##"<circle cx="75" cy="100" r="16" fill="none" stroke="#222" stroke-width="3"/>"##,
+		r##"<circle cx="125" cy="100" r="16" fill="none" stroke="#222" stroke-width="3"/>"##,
+		r##"<path d="M91 100 h18" stroke="#222" stroke-width="3"/>"##,
+	],
+	&[
+		r##"<path d="M112 134 l30 10 v12" stroke="#6b3e1e" stroke-width="5" fill="none"/>"##,
+	],
+	&[
+		r##"<path d="M80 40 l20 10 l-20 10 Z M120 40 l-20 10 l20 10 Z" fill="#e33"/>"##,
+	],
+	&[
+		r##"<circle cx="68" cy="122" r="2" fill="#333"/>"##,
+		r##"<circle cx="76" cy="126" r="2" fill="#333"/>"##,
+		r##"<circle cx="124" cy="126" r="2" fill="#333"/>"##,
+		r##"<circle cx="132" cy="122" r="2" fill="#333"/>"##,
+	],
+];
+
+/// The image a gene picks out of `images`, the same way `KittyAvatar` does.
+fn variant<'a>(images: &[&'a [&'a str]; 4], gene: u8) -> &'a [&'a str] {
+	images[gene as usize % images.len()]
+}
+
+/// Render a 200x200 SVG image of a kitty, one element per line.
+pub fn render(phenotype: &Phenotype) -> String {
+	let colour = BODY_COLOURS[phenotype.body as usize % BODY_COLOURS.len()];
+	let mut lines = vec![
+		r#"<svg xmlns="http://www.w3.org/2000/svg" width="200" height="200" viewBox="0 0 200 200">"#.to_string(),
+		format!(r#"<path d="M50 80 L60 30 L90 60 Z M150 80 L140 30 L110 60 Z" fill="{}"/>"#, colour),
+		format!(r##"<ellipse cx="100" cy="110" rx="62" ry="70" fill="{}" stroke="#333" stroke-width="3"/>"##, colour),
+	];
+
+	lines.extend(variant(&FUR, phenotype.fur).iter().map(|line| line.to_string()));
+	lines.push(NOSE.to_string());
+	lines.extend(variant(&MOUTHS, phenotype.mouth).iter().map(|line| line.to_string()));
+	lines.extend(variant(&EYES, phenotype.eyes).iter().map(|line| line.to_string()));
+	lines.extend(variant(&ACCESSORIES, phenotype.accessory).iter().map(|line| line.to_string()));
+
+	lines.push("</svg>".to_string());
+	lines.join("\n") + "\n"
+}
@@ -0,0 +1,41 @@
+use super::render;
+use pallet_kitties::Kitty;
+
+fn render_dna(genes: [u8; 5]) -> String {
+	let mut dna = [0u8; 16];
+	dna[..5].copy_from_slice(&genes);
+	render(&Kitty(dna).phenotype())
+}
+
+#[test]
+fn glasses_kitty_snapshot() {
+	assert_eq!(render_dna([0x00, 0x00, 0x00, 0x00, 0x00]), include_str!("snapshots/glasses.svg"));
+}
+
+#[test]
+fn pipe_kitty_snapshot() {
+	assert_eq!(render_dna([0x2a, 0x7f, 0xc5, 0x13, 0xfe]), include_str!("snapshots/pipe.svg"));
+}
+
+#[test]
+fn bow_kitty_snapshot() {
+	assert_eq!(render_dna([0xff, 0x05, 0x06, 0x02, 0x04]), include_str!("snapshots/bow.svg"));
+}
+
+#[test]
+fn freckles_kitty_snapshot() {
+	assert_eq!(render_dna([0x11, 0x02, 0x07, 0x01, 0x03]), include_str!("snapshots/freckles.svg"));
+}
+
+#[test]
+fn genes_pick_one_of_four_images() {
+	assert_eq!(render_dna([0x04, 0x05, 0x06, 0x07, 0xfc]), render_dna([0x00, 0x01, 0x02, 0x03, 0x00]));
+}
+
+#[test]
+fn only_expressed_genes_matter() {
+	let mut dna = [0x2au8; 16];
+	let first = render(&Kitty(dna).phenotype());
+	dna[10] = 0xff;
+	assert_eq!(render(&Kitty(dna).phenotype()), first);
+}
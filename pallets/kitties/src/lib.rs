@@ -1,6 +1,9 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 pub use pallet::*;
+pub mod traits;
+pub mod migrations;
+mod test_runtime;
 #[cfg(test)]
 mod mock;
 #[cfg(test)]
@@ -8,17 +11,169 @@ mod tests;
 
 #[frame_support::pallet]
 pub mod pallet {
-    use frame_support::{pallet_prelude::*, traits::{Randomness, ReservableCurrency, Currency, ExistenceRequirement}};
+    use frame_support::{pallet_prelude::*, transactional, traits::{Randomness, ReservableCurrency, Currency, ExistenceRequirement, tokens::fungibles}};
     use frame_system::pallet_prelude::*;
-    use sp_runtime::traits::{AtLeast32Bit, MaybeDisplay, Bounded};
+    use sp_runtime::{
+        Permill, RuntimeDebug,
+        traits::{AtLeast32Bit, MaybeDisplay, Bounded, CheckedAdd, Hash, One, Zero, Verify, IdentifyAccount},
+        transaction_validity::{InvalidTransaction, TransactionPriority, TransactionSource, TransactionValidity, ValidTransaction},
+    };
     use codec::{Encode, Decode};
     use sp_io::hashing::blake2_128;
-    use sp_std::fmt::Debug;
+    use sp_std::{fmt::Debug, prelude::*};
+    use crate::traits::{OnKittyCreated, OnKittyTransferred, OnKittyBurned};
+    #[cfg(feature = "std")]
+    use serde::{Serialize, Deserialize};
 
-    #[derive(Encode, Decode)]
+    const STARTER_KITTY_PRIORITY: TransactionPriority = 100;
+
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
     pub struct Kitty(pub [u8;16]);
     //type KittyIndex = u32;
 
+    /// Everything stored about a single kitty.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+    pub struct KittyInfo<AccountId, Balance> {
+        pub dna: Kitty,
+        pub owner: AccountId,
+        /// The native price the kitty is listed at, `None` when it is not for sale.
+        pub price: Option<Balance>,
+        /// The account the kitty's deposit is reserved from. It stays the same when the kitty is
+        /// transferred, and becomes the buyer when it is sold.
+        pub depositor: AccountId,
+        /// The amount reserved from `depositor`, zero for starter and bred kitties.
+        pub deposit: Balance,
+    }
+
+    /// The storage layout of the pallet.
+    #[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+    pub enum Releases {
+        /// `Kitties`, `Owner` and `KittiesPrice` kept in three separate maps.
+        V1,
+        /// A single `Kitties` map of `KittyInfo` keyed by `(CollectionId, KittyIndex)` and
+        /// recording each kitty's deposit, indexed by owner and by listing.
+        V2,
+    }
+
+    impl Default for Releases {
+        fn default() -> Self {
+            Releases::V1
+        }
+    }
+
+    /// The traits expressed by a kitty's DNA, one gene per byte as `KittyCards` draws them.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+    pub struct Phenotype {
+        pub body: u8,
+        pub eyes: u8,
+        pub accessory: u8,
+        pub fur: u8,
+        pub mouth: u8,
+    }
+
+    impl Kitty {
+        pub fn phenotype(&self) -> Phenotype {
+            Phenotype {
+                body: self.0[0],
+                eyes: self.0[1],
+                accessory: self.0[2],
+                fur: self.0[3],
+                mouth: self.0[4],
+            }
+        }
+    }
+
+    /// A series of kitties launched by a third-party creator.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+    pub struct CollectionInfo<AccountId, Balance, KittyIndex> {
+        /// The creator, who administers the collection and receives royalties.
+        pub owner: AccountId,
+        /// The amount reserved from the creator for this collection.
+        pub deposit: Balance,
+        /// The maximum number of kitties that can be minted into the collection.
+        pub max_supply: KittyIndex,
+        /// The share of every sale price paid to the creator.
+        pub royalty: Permill,
+        /// Arbitrary metadata, e.g. an IPFS hash describing the series.
+        pub metadata: Vec<u8>,
+    }
+
+    /// A sale offer signed by the seller off-chain and filled on-chain by a buyer.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+    pub struct SellOrder<AccountId, KittyId, Balance, BlockNumber> {
+        pub seller: AccountId,
+        pub kitty_id: KittyId,
+        pub price: Balance,
+        /// The last block at which the order can be filled.
+        pub expiry: BlockNumber,
+        /// Seller-chosen nonce; each one can be filled or cancelled only once.
+        pub nonce: u64,
+    }
+
+    /// A committed `breed` call, whose child is minted once the owner reveals their secret.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+    pub struct BreedCommitment<AccountId, KittyId, Hash, Balance> {
+        pub owner: AccountId,
+        pub parents: (KittyId, KittyId),
+        /// The hash of the secret the owner reveals with `reveal_breed`.
+        pub secret_hash: Hash,
+        /// The amount reserved from `owner`, forfeited if the secret is never revealed.
+        pub deposit: Balance,
+        /// The randomness drawn at the reveal block, `None` until that block is reached.
+        pub seed: Option<Hash>,
+    }
+
+    /// What a kitty changed hands for.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+    pub enum SalePrice<Balance, AssetId, AssetBalance> {
+        Native(Balance),
+        Asset(AssetId, AssetBalance),
+    }
+
+    /// One change of ownership in a kitty's provenance.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+    #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+    pub struct HistoryEntry<BlockNumber, AccountId, Price> {
+        pub block_number: BlockNumber,
+        pub from: AccountId,
+        pub to: AccountId,
+        /// `None` for a plain transfer.
+        pub price: Option<Price>,
+    }
+
+    pub type SalePriceOf<T> = SalePrice<BalanceOf<T>, AssetIdOf<T>, AssetBalanceOf<T>>;
+    pub type HistoryEntryOf<T> = HistoryEntry<
+        <T as frame_system::Config>::BlockNumber,
+        <T as frame_system::Config>::AccountId,
+        SalePriceOf<T>,
+    >;
+
+    pub type KittyInfoOf<T> = KittyInfo<<T as frame_system::Config>::AccountId, BalanceOf<T>>;
+    pub type BreedCommitmentOf<T> = BreedCommitment<
+        <T as frame_system::Config>::AccountId,
+        KittyIdOf<T>,
+        <T as frame_system::Config>::Hash,
+        BalanceOf<T>,
+    >;
+
+    /// A kitty is identified by its collection and its index inside that collection.
+    ///
+    /// Kitties that are created, bred or claimed rather than minted by a creator belong to the
+    /// common collection `0`; user collections are numbered from `1`.
+    pub type KittyIdOf<T> = (<T as Config>::CollectionId, <T as Config>::KittyIndex);
+
+    pub type SellOrderOf<T> = SellOrder<
+        <T as frame_system::Config>::AccountId,
+        KittyIdOf<T>,
+        BalanceOf<T>,
+        <T as frame_system::Config>::BlockNumber,
+    >;
+
     #[pallet::config]
     pub trait Config: pallet_balances::Config + frame_system::Config {
         type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
@@ -27,15 +182,69 @@ pub mod pallet {
         + Copy + Encode;
         type Currency: Currency<Self::AccountId> + ReservableCurrency<Self::AccountId>;
         type KittyDepositBase: Get<BalanceOf<Self>>;
+        type CollectionId: Parameter + Member + MaybeSerializeDeserialize + Debug + Default + MaybeDisplay + AtLeast32Bit
+        + Copy + Encode;
+        /// The amount reserved from a creator when opening a collection.
+        type CollectionDeposit: Get<BalanceOf<Self>>;
+        /// The maximum length of a collection's metadata.
+        type MaxMetadataLength: Get<u32>;
+        /// The signature a seller puts on an off-chain `SellOrder`.
+        type OffchainSignature: Verify<Signer = Self::OffchainPublic> + Parameter;
+        /// The key an order signature is checked against, resolving to the seller's account.
+        type OffchainPublic: IdentifyAccount<AccountId = Self::AccountId>;
+        /// The fungible assets kitties can be priced in besides the native currency.
+        type Assets: fungibles::Transfer<Self::AccountId>;
+        /// Handlers notified when a kitty is created.
+        type OnKittyCreated: OnKittyCreated<Self::AccountId, KittyIdOf<Self>>;
+        /// Handlers notified when a kitty is transferred or sold.
+        type OnKittyTransferred: OnKittyTransferred<Self::AccountId, KittyIdOf<Self>>;
+        /// Handlers notified when a kitty is burned.
+        type OnKittyBurned: OnKittyBurned<Self::AccountId, KittyIdOf<Self>>;
+        /// The number of history entries kept per kitty; the oldest ones are pruned first.
+        type MaxHistoryLength: Get<u32>;
+        /// The number of blocks between a `breed` commitment and the block whose randomness seeds
+        /// the child's DNA.
+        type BreedRevealDelay: Get<Self::BlockNumber>;
+        /// The number of blocks after that the owner has to reveal their secret.
+        type BreedRevealWindow: Get<Self::BlockNumber>;
+        /// The amount reserved for a breed commitment until its secret is revealed.
+        type BreedDeposit: Get<BalanceOf<Self>>;
+        /// The maximum number of breed commitments drawing their randomness at the same block.
+        type MaxBreedsPerBlock: Get<u32>;
+        /// The maximum number of starter kitties that can be claimed in a single block.
+        type StarterClaimsPerBlock: Get<u32>;
+        /// The maximum number of starter kitties that can be claimed over the life of the chain.
+        type MaxStarterKitties: Get<u32>;
     }
 
     #[pallet::event]
     #[pallet::metadata(T::AccountId = "AccountId")]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
-        KittyCreate(T::AccountId, T::KittyIndex),
-        KittyTransfer(T::AccountId, T::AccountId, T::KittyIndex),
-        KittySale(T::AccountId, T::KittyIndex, Option<BalanceOf<T>>),
+        KittyCreate(T::AccountId, KittyIdOf<T>),
+        KittyTransfer(T::AccountId, T::AccountId, KittyIdOf<T>),
+        KittySale(T::AccountId, KittyIdOf<T>, Option<BalanceOf<T>>),
+        CollectionCreate(T::AccountId, T::CollectionId),
+        CollectionMint(T::AccountId, T::CollectionId, T::KittyIndex),
+        CollectionMetadataSet(T::CollectionId),
+        OrderFill(T::AccountId, T::AccountId, KittyIdOf<T>, BalanceOf<T>),
+        OrderCancel(T::AccountId, u64),
+        KittyAssetSale(T::AccountId, KittyIdOf<T>, AssetIdOf<T>, AssetBalanceOf<T>),
+        /// [owner, commit_id, block from which the secret can be revealed]
+        BreedCommit(T::AccountId, u64, T::BlockNumber),
+        /// A kitty changed hands for a price, through `buy_kitty` or `fill_order`. [seller, buyer, kitty_id, price]
+        KittySold(T::AccountId, T::AccountId, KittyIdOf<T>, SalePriceOf<T>),
+        /// A collection was destroyed and its deposit returned. [collection_id]
+        CollectionDestroy(T::CollectionId),
+        /// A kitty was burned and its deposit returned. [owner, kitty_id]
+        KittyBurn(T::AccountId, KittyIdOf<T>),
+        /// A breed commitment was revealed and its child minted. [owner, commit_id, kitty_id]
+        KittyBred(T::AccountId, u64, KittyIdOf<T>),
+        /// A breed commitment was revealed, but a parent is gone or the common collection is full,
+        /// so no child was minted. The deposit is returned. [owner, commit_id]
+        BreedRevealFailed(T::AccountId, u64),
+        /// A breed commitment was not revealed in time and its deposit forfeited. [owner, commit_id]
+        BreedExpired(T::AccountId, u64),
     }
 
     #[pallet::error]
@@ -47,30 +256,179 @@ pub mod pallet {
         BalanceLitter,
         FromSameTo,
         NotKittySale,
+        CollectionsCountOverflow,
+        InvalidCollectionId,
+        NotCollectionOwner,
+        CollectionSoldOut,
+        MetadataTooLong,
+        InvalidSignature,
+        OrderExpired,
+        OrderNonceUsed,
+        KittyLocked,
+        StarterAlreadyClaimed,
+        TooManyStarterClaims,
+        StarterKittiesExhausted,
+        NotNewAccount,
+        TooManyBreeds,
+        InvalidBreedCommit,
+        BreedNotRevealable,
+        SecretMismatch,
     }
 
-    type BalanceOf<T> =
+    pub type BalanceOf<T> =
 	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+    pub type AssetIdOf<T> =
+        <<T as Config>::Assets as fungibles::Inspect<<T as frame_system::Config>::AccountId>>::AssetId;
+    pub type AssetBalanceOf<T> =
+        <<T as Config>::Assets as fungibles::Inspect<<T as frame_system::Config>::AccountId>>::Balance;
 
     
     #[pallet::pallet]
     #[pallet::generate_store(pub(super) trait Store)]
     pub struct Pallet<T>(_);
 
+    /// The next kitty index inside each collection.
     #[pallet::storage]
     #[pallet::getter(fn kitties_count)]
-    pub type KittiesCount<T: Config> = StorageValue<_, T::KittyIndex>;
+    pub type KittiesCount<T: Config> = StorageMap<_, Blake2_128Concat, T::CollectionId, T::KittyIndex, ValueQuery>;
 
+    /// Listings priced in a fungible asset rather than the native currency.
     #[pallet::storage]
-    #[pallet::getter(fn kitties_price)]
-    pub type KittiesPrice<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, Option<BalanceOf<T>>, ValueQuery>;
+    #[pallet::getter(fn kitties_asset_price)]
+    pub type KittiesAssetPrice<T: Config> = StorageMap<_, Blake2_128Concat, KittyIdOf<T>, (AssetIdOf<T>, AssetBalanceOf<T>)>;
+
     #[pallet::storage]
     #[pallet::getter(fn kitties)]
-    pub type Kitties<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, Option<Kitty>, ValueQuery>;
+    pub type Kitties<T: Config> = StorageMap<_, Blake2_128Concat, KittyIdOf<T>, KittyInfoOf<T>>;
 
     #[pallet::storage]
-    #[pallet::getter(fn owner)]
-    pub type Owner<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, Option<T::AccountId>, ValueQuery>;
+    #[pallet::getter(fn storage_version)]
+    pub(super) type StorageVersion<T: Config> = StorageValue<_, Releases, ValueQuery>;
+
+    /// The id of the latest user collection.
+    #[pallet::storage]
+    #[pallet::getter(fn collections_count)]
+    pub type CollectionsCount<T: Config> = StorageValue<_, T::CollectionId, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn collections)]
+    pub type Collections<T: Config> = StorageMap<_, Blake2_128Concat, T::CollectionId,
+        CollectionInfo<T::AccountId, BalanceOf<T>, T::KittyIndex>>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn kitty_history)]
+    pub type KittyHistory<T: Config> = StorageMap<_, Blake2_128Concat, KittyIdOf<T>, Vec<HistoryEntryOf<T>>, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn breed_commits_count)]
+    pub type BreedCommitsCount<T: Config> = StorageValue<_, u64, ValueQuery>;
+
+    /// Breed commitments that have not been revealed yet, by id.
+    #[pallet::storage]
+    #[pallet::getter(fn breed_commits)]
+    pub type BreedCommits<T: Config> = StorageMap<_, Twox64Concat, u64, BreedCommitmentOf<T>>;
+
+    /// The breed commitments due at a given block: those without a seed draw it there, the rest
+    /// expire there.
+    #[pallet::storage]
+    #[pallet::getter(fn pending_breeds)]
+    pub type PendingBreeds<T: Config> = StorageMap<_, Twox64Concat, T::BlockNumber, Vec<u64>, ValueQuery>;
+
+    /// The kitties each account holds, so they can be listed without walking every kitty.
+    #[pallet::storage]
+    pub type OwnedKitties<T: Config> = StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Blake2_128Concat, KittyIdOf<T>, ()>;
+
+    /// Kitties listed for a native price.
+    #[pallet::storage]
+    pub type Listings<T: Config> = StorageMap<_, Blake2_128Concat, KittyIdOf<T>, ()>;
+
+    /// Kitties held as collateral by another pallet; they cannot be transferred or sold.
+    #[pallet::storage]
+    #[pallet::getter(fn is_locked)]
+    pub type LockedKitties<T: Config> = StorageMap<_, Blake2_128Concat, KittyIdOf<T>, bool, ValueQuery>;
+
+    /// Accounts that have claimed their free starter kitty.
+    #[pallet::storage]
+    #[pallet::getter(fn starter_claimed)]
+    pub type StarterClaimed<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, bool, ValueQuery>;
+
+    /// The block of the latest starter claims and how many were made in it.
+    #[pallet::storage]
+    #[pallet::getter(fn starter_claims)]
+    pub(super) type StarterClaims<T: Config> = StorageValue<_, (T::BlockNumber, u32), ValueQuery>;
+
+    /// The number of starter kitties claimed so far.
+    #[pallet::storage]
+    #[pallet::getter(fn starter_kitties_claimed)]
+    pub type StarterKittiesClaimed<T: Config> = StorageValue<_, u32, ValueQuery>;
+
+    /// Sell order nonces that have been filled or cancelled.
+    #[pallet::storage]
+    #[pallet::getter(fn used_nonces)]
+    pub type UsedNonces<T: Config> = StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Twox64Concat, u64, bool, ValueQuery>;
+
+    #[pallet::genesis_config]
+    pub struct GenesisConfig<T: Config> {
+        /// Kitties minted at genesis as `(owner, dna, price)`; a price lists the kitty for sale.
+        pub kitties: Vec<(T::AccountId, [u8; 16], Option<BalanceOf<T>>)>,
+    }
+
+    #[cfg(feature = "std")]
+    impl<T: Config> Default for GenesisConfig<T> {
+        fn default() -> Self {
+            Self { kitties: Vec::new() }
+        }
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+        fn build(&self) {
+            for (owner, dna, price) in &self.kitties {
+                let kitty_id = Pallet::<T>::next_kitty_id(Zero::zero()).expect("too many genesis kitties");
+                let deposit = T::KittyDepositBase::get();
+                T::Currency::reserve(owner, deposit).expect("genesis kitty owner cannot afford the deposit");
+
+                Pallet::<T>::insert_kitty(kitty_id, owner, *dna, deposit);
+                Pallet::<T>::set_price(kitty_id, *price);
+            }
+            StorageVersion::<T>::put(Releases::V2);
+        }
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(now: T::BlockNumber) -> Weight {
+            let pending = PendingBreeds::<T>::take(now);
+            let weight = T::DbWeight::get().reads_writes(1 + 3 * pending.len() as u64, 1 + 3 * pending.len() as u64);
+
+            for commit_id in pending {
+                Self::draw_or_expire_breed(now, commit_id);
+            }
+
+            weight
+        }
+
+        fn on_runtime_upgrade() -> Weight {
+            let mut weight = T::DbWeight::get().reads(1);
+            if Self::storage_version() == Releases::V1 {
+                weight = weight.saturating_add(crate::migrations::v2::migrate::<T>());
+            }
+            weight
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<(), &'static str> {
+            match Self::storage_version() {
+                Releases::V1 => crate::migrations::v2::pre_migrate::<T>(),
+                Releases::V2 => Ok(()),
+            }
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade() -> Result<(), &'static str> {
+            crate::migrations::v2::post_migrate::<T>()
+        }
+    }
 
     #[pallet::call]
     impl<T: Config> Pallet<T> {
@@ -78,128 +436,666 @@ pub mod pallet {
         pub fn create(origin: OriginFor<T>) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
-            let kitty_id = match Self::kitties_count() {
-                Some(id) => {
-                    //id = T::KittyIndex::get();
-                    ensure!(id != T::KittyIndex::max_value(), Error::<T>::KittiesCountOverflow);
-                    id
-                },
-                None => {
-                    0u32.into()
-                }
-            };
+            let kitty_id = Self::next_kitty_id(Zero::zero())?;
             let deposit = T::KittyDepositBase::get();
             T::Currency::reserve(&who,deposit.clone()).map_err(|_| Error::<T>::BalanceLitter)?;
             let dna = Self::random_value(&who);
 
-            Kitties::<T>::insert(kitty_id, Some(Kitty(dna)));
-            Owner::<T>::insert(kitty_id, Some(who.clone()));
-
-            KittiesCount::<T>::put(kitty_id+1u32.into());
+            Self::insert_kitty(kitty_id, &who, dna, deposit);
 
             Self::deposit_event(Event::KittyCreate(who, kitty_id));
 
             Ok(())
         }
 
+        //转让kitty，同时撤销它的出售挂单，新主人需要重新挂单
         #[pallet::weight(0)]
-        pub fn transfer(origin: OriginFor<T>, new_owner: T::AccountId, kitty_id: T::KittyIndex) -> DispatchResult {
+        pub fn transfer(origin: OriginFor<T>, new_owner: T::AccountId, kitty_id: KittyIdOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Self::do_transfer(&who, &new_owner, kitty_id)
+        }
+
+        //销毁自己的kitty，退还押金，编号不再复用
+        #[pallet::weight(0)]
+        pub fn burn(origin: OriginFor<T>, kitty_id: KittyIdOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Self::do_burn(&who, kitty_id)
+        }
+
+        //先提交秘密值的哈希并预留押金，子代DNA由秘密值和揭示区块的随机数共同决定
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(6, 4))]
+        pub fn breed(
+            origin: OriginFor<T>,
+            kitty_id_1: KittyIdOf<T>,
+            kitty_id_2: KittyIdOf<T>,
+            secret_hash: T::Hash,
+        ) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
-            ensure!(Some(who.clone()) == Owner::<T>::get(kitty_id), Error::<T>::NotOwner);
+            ensure!(kitty_id_1 != kitty_id_2, Error::<T>::SameParentIndex);
+
+            ensure!(Self::kitties(kitty_id_1).is_some(), Error::<T>::InvalidKittyIndex);
+            ensure!(Self::kitties(kitty_id_2).is_some(), Error::<T>::InvalidKittyIndex);
+            Self::next_kitty_id(Zero::zero())?;
 
-            Owner::<T>::insert(kitty_id, Some(new_owner.clone()));
+            let reveal_at = <frame_system::Pallet<T>>::block_number() + T::BreedRevealDelay::get();
+            let scheduled = PendingBreeds::<T>::decode_len(reveal_at).unwrap_or(0);
+            ensure!(scheduled < T::MaxBreedsPerBlock::get() as usize, Error::<T>::TooManyBreeds);
 
-            Self::deposit_event(Event::KittyTransfer(who, new_owner, kitty_id));
+            let deposit = T::BreedDeposit::get();
+            T::Currency::reserve(&who, deposit).map_err(|_| Error::<T>::BalanceLitter)?;
+
+            let commit_id = Self::breed_commits_count();
+            BreedCommits::<T>::insert(commit_id, BreedCommitment {
+                owner: who.clone(),
+                parents: (kitty_id_1, kitty_id_2),
+                secret_hash,
+                deposit,
+                seed: None,
+            });
+            PendingBreeds::<T>::append(reveal_at, commit_id);
+            BreedCommitsCount::<T>::put(commit_id.wrapping_add(1));
+
+            Self::deposit_event(Event::BreedCommit(who, commit_id, reveal_at));
 
             Ok(())
         }
 
-        #[pallet::weight(0)]
-        pub fn breed(origin: OriginFor<T>, kitty_id_1: T::KittyIndex, kitty_id_2: T::KittyIndex) -> DispatchResult {
+        //在揭示窗口内公布秘密值，铸造子代并退还押金
+        #[pallet::weight(10_000 + T::DbWeight::get().reads_writes(6, 5))]
+        pub fn reveal_breed(origin: OriginFor<T>, commit_id: u64, secret: [u8; 32]) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
-            ensure!(kitty_id_1 != kitty_id_2, Error::<T>::SameParentIndex);
+            let commitment = Self::breed_commits(commit_id).ok_or(Error::<T>::InvalidBreedCommit)?;
+            ensure!(commitment.owner == who, Error::<T>::NotOwner);
+            let seed = commitment.seed.ok_or(Error::<T>::BreedNotRevealable)?;
+            ensure!(T::Hashing::hash(&secret) == commitment.secret_hash, Error::<T>::SecretMismatch);
 
-            let kitty1 = Self::kitties(kitty_id_1).ok_or(Error::<T>::InvalidKittyIndex)?;
-            let kitty2 = Self::kitties(kitty_id_2).ok_or(Error::<T>::InvalidKittyIndex)?;
-            
-            let kitty_id = match Self::kitties_count() {
-                Some(id) => {
-                    ensure!(id != T::KittyIndex::max_value(), Error::<T>::KittiesCountOverflow);
-                    id
-                },
+            BreedCommits::<T>::remove(commit_id);
+            T::Currency::unreserve(&who, commitment.deposit);
+
+            match Self::mint_child(&who, commitment.parents, seed, &secret) {
+                Some(kitty_id) => Self::deposit_event(Event::KittyBred(who, commit_id, kitty_id)),
+                None => Self::deposit_event(Event::BreedRevealFailed(who, commit_id)),
+            }
+
+            Ok(())
+        }
+
+        //买入kitty
+        #[pallet::weight(0)]
+        #[transactional]
+        pub fn buy_kitty(origin: OriginFor<T>, kitty_id: KittyIdOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin.clone())?;
+
+            let kitty = Self::kitties(kitty_id).ok_or(Error::<T>::InvalidKittyIndex)?;
+            let from = kitty.owner;
+            ensure!(who.clone() != from, Error::<T>::FromSameTo);
+
+            match Self::kitties_asset_price(kitty_id) {
+                Some((asset_id, price)) => Self::do_buy_with_asset(&who, &from, kitty_id, asset_id, price)?,
                 None => {
-                    1u32.into()
-                }
+                    let price = kitty.price.ok_or(Error::<T>::NotKittySale)?;
+                    Self::do_buy(&who, &from, kitty_id, price)?;
+                },
+            }
+
+            Ok(())
+        }
+
+        //卖出kitty
+        #[pallet::weight(0)]
+        pub fn sell_kitty(origin: OriginFor<T>, kitty_id: KittyIdOf<T>, amount: Option<BalanceOf<T>>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(Some(who.clone()) == Self::owner(kitty_id), Error::<T>::FromSameTo);
+            ensure!(!Self::is_locked(kitty_id), Error::<T>::KittyLocked);
+
+            Self::set_price(kitty_id, amount);
+            KittiesAssetPrice::<T>::remove(kitty_id);
+            Self::deposit_event(Event::KittySale(who, kitty_id, amount));
+
+            Ok(())
+        }
+
+        //以资产代币标价卖出kitty
+        #[pallet::weight(0)]
+        pub fn sell_kitty_for_asset(
+            origin: OriginFor<T>,
+            kitty_id: KittyIdOf<T>,
+            asset_id: AssetIdOf<T>,
+            price: AssetBalanceOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(Some(who.clone()) == Self::owner(kitty_id), Error::<T>::FromSameTo);
+            ensure!(!Self::is_locked(kitty_id), Error::<T>::KittyLocked);
+
+            KittiesAssetPrice::<T>::insert(kitty_id, (asset_id, price));
+            Self::set_price(kitty_id, None);
+            Self::deposit_event(Event::KittyAssetSale(who, kitty_id, asset_id, price));
+
+            Ok(())
+        }
+
+        //创建系列
+        #[pallet::weight(0)]
+        pub fn create_collection(
+            origin: OriginFor<T>,
+            max_supply: T::KittyIndex,
+            royalty: Permill,
+            metadata: Vec<u8>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(metadata.len() <= T::MaxMetadataLength::get() as usize, Error::<T>::MetadataTooLong);
+            let collection_id = Self::collections_count().checked_add(&One::one())
+                .ok_or(Error::<T>::CollectionsCountOverflow)?;
+
+            let deposit = T::CollectionDeposit::get();
+            T::Currency::reserve(&who, deposit).map_err(|_| Error::<T>::BalanceLitter)?;
+
+            Collections::<T>::insert(collection_id, CollectionInfo {
+                owner: who.clone(),
+                deposit,
+                max_supply,
+                royalty,
+                metadata,
+            });
+            CollectionsCount::<T>::put(collection_id);
+
+            Self::deposit_event(Event::CollectionCreate(who, collection_id));
+
+            Ok(())
+        }
+
+        //在系列中铸造kitty，只有系列创建者可以调用
+        #[pallet::weight(0)]
+        pub fn mint(origin: OriginFor<T>, collection_id: T::CollectionId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let collection = Self::collections(collection_id).ok_or(Error::<T>::InvalidCollectionId)?;
+            ensure!(collection.owner == who, Error::<T>::NotCollectionOwner);
+            ensure!(Self::kitties_count(collection_id) < collection.max_supply, Error::<T>::CollectionSoldOut);
+
+            let kitty_id = Self::next_kitty_id(collection_id)?;
+            let deposit = T::KittyDepositBase::get();
+            T::Currency::reserve(&who, deposit).map_err(|_| Error::<T>::BalanceLitter)?;
+            let dna = Self::random_value(&who);
+
+            Self::insert_kitty(kitty_id, &who, dna, deposit);
+
+            Self::deposit_event(Event::CollectionMint(who, collection_id, kitty_id.1));
+
+            Ok(())
+        }
+
+        //修改系列元数据
+        #[pallet::weight(0)]
+        pub fn set_collection_metadata(
+            origin: OriginFor<T>,
+            collection_id: T::CollectionId,
+            metadata: Vec<u8>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(metadata.len() <= T::MaxMetadataLength::get() as usize, Error::<T>::MetadataTooLong);
+            Collections::<T>::try_mutate(collection_id, |maybe_collection| -> DispatchResult {
+                let collection = maybe_collection.as_mut().ok_or(Error::<T>::InvalidCollectionId)?;
+                ensure!(collection.owner == who, Error::<T>::NotCollectionOwner);
+                collection.metadata = metadata;
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::CollectionMetadataSet(collection_id));
+
+            Ok(())
+        }
+
+        //销毁系列并退还押金，已铸造的kitty保留，但不再支付版税
+        #[pallet::weight(0)]
+        pub fn destroy_collection(origin: OriginFor<T>, collection_id: T::CollectionId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let collection = Self::collections(collection_id).ok_or(Error::<T>::InvalidCollectionId)?;
+            ensure!(collection.owner == who, Error::<T>::NotCollectionOwner);
+
+            Collections::<T>::remove(collection_id);
+            T::Currency::unreserve(&who, collection.deposit);
+
+            Self::deposit_event(Event::CollectionDestroy(collection_id));
+
+            Ok(())
+        }
+
+        //成交卖家链下签名的订单
+        #[pallet::weight(0)]
+        #[transactional]
+        pub fn fill_order(origin: OriginFor<T>, order: SellOrderOf<T>, signature: T::OffchainSignature) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(signature.verify(&Self::sell_order_message(&order)[..], &order.seller), Error::<T>::InvalidSignature);
+            ensure!(!Self::used_nonces(&order.seller, order.nonce), Error::<T>::OrderNonceUsed);
+            ensure!(<frame_system::Pallet<T>>::block_number() <= order.expiry, Error::<T>::OrderExpired);
+            ensure!(Some(order.seller.clone()) == Self::owner(order.kitty_id), Error::<T>::NotOwner);
+            ensure!(who != order.seller, Error::<T>::FromSameTo);
+
+            Self::do_buy(&who, &order.seller, order.kitty_id, order.price)?;
+            UsedNonces::<T>::insert(&order.seller, order.nonce, true);
+
+            Self::deposit_event(Event::OrderFill(order.seller, who, order.kitty_id, order.price));
+
+            Ok(())
+        }
+
+        //取消链下订单，使该nonce失效
+        #[pallet::weight(0)]
+        pub fn cancel_order(origin: OriginFor<T>, nonce: u64) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(!Self::used_nonces(&who, nonce), Error::<T>::OrderNonceUsed);
+            UsedNonces::<T>::insert(&who, nonce, true);
+
+            Self::deposit_event(Event::OrderCancel(who, nonce));
+
+            Ok(())
+        }
+
+        //新账户免费领取一只kitty，无需手续费和押金，由账户签名证明所有权；全链领取总数有上限
+        #[pallet::weight(0)]
+        pub fn claim_starter_kitty(
+            origin: OriginFor<T>,
+            who: T::AccountId,
+            signature: T::OffchainSignature,
+        ) -> DispatchResult {
+            ensure_none(origin)?;
+
+            Self::check_starter_claim(&who, &signature)?;
+            let kitty_id = Self::next_kitty_id(Zero::zero())?;
+            let dna = Self::random_value(&who);
+
+            Self::insert_kitty(kitty_id, &who, dna, Zero::zero());
+            StarterClaimed::<T>::insert(&who, true);
+            let now = <frame_system::Pallet<T>>::block_number();
+            StarterClaims::<T>::put((now, Self::starter_claims_at(now) + 1));
+            StarterKittiesClaimed::<T>::mutate(|claimed| *claimed += 1);
+
+            Self::deposit_event(Event::KittyCreate(who, kitty_id));
+
+            Ok(())
+        }
+    }
+
+    #[pallet::validate_unsigned]
+    impl<T: Config> ValidateUnsigned for Pallet<T> {
+        type Call = Call<T>;
+
+        fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+            if let Call::claim_starter_kitty(who, signature) = call {
+                Self::check_starter_claim(who, signature).map_err(|e| match e {
+                    Error::<T>::InvalidSignature => InvalidTransaction::BadProof,
+                    Error::<T>::StarterAlreadyClaimed | Error::<T>::NotNewAccount => InvalidTransaction::Stale,
+                    _ => InvalidTransaction::ExhaustsResources,
+                })?;
+
+                ValidTransaction::with_tag_prefix("kitty-starter")
+                    .priority(STARTER_KITTY_PRIORITY)
+                    .and_provides(who)
+                    .longevity(5)
+                    .propagate(true)
+                    .build()
+            } else {
+                InvalidTransaction::Call.into()
+            }
+        }
+    }
+    
+    impl<T: Config> Pallet<T> {
+        /// Handle a breed commitment due at `now`: draw its seed and give the owner
+        /// `BreedRevealWindow` blocks to reveal, or, if it already has a seed, forfeit its deposit.
+        fn draw_or_expire_breed(now: T::BlockNumber, commit_id: u64) {
+            let mut commitment = match Self::breed_commits(commit_id) {
+                Some(commitment) => commitment,
+                // Revealed already.
+                None => return,
             };
 
-            let dna_1 = kitty1.0;
-            let dna_2 = kitty2.0;
+            if commitment.seed.is_none() {
+                let subject = (b"kitty-breed", commit_id).encode();
+                commitment.seed = Some(T::Randomness::random(&subject).0);
+                BreedCommits::<T>::insert(commit_id, commitment);
+                PendingBreeds::<T>::append(now + T::BreedRevealWindow::get(), commit_id);
+            } else {
+                BreedCommits::<T>::remove(commit_id);
+                let _ = T::Currency::slash_reserved(&commitment.owner, commitment.deposit);
+                Self::deposit_event(Event::BreedExpired(commitment.owner, commit_id));
+            }
+        }
+
+        /// Mix the parents' DNA, selecting genes with the seed and the owner's secret, and mint the
+        /// child. The seed is fixed before the secret is revealed and the secret before the seed is
+        /// drawn, so neither the owner nor a block author can pick the child.
+        fn mint_child(
+            owner: &T::AccountId,
+            (kitty_id_1, kitty_id_2): (KittyIdOf<T>, KittyIdOf<T>),
+            seed: T::Hash,
+            secret: &[u8; 32],
+        ) -> Option<KittyIdOf<T>> {
+            let dna_1 = Self::kitties(kitty_id_1)?.dna.0;
+            let dna_2 = Self::kitties(kitty_id_2)?.dna.0;
+            let kitty_id = Self::next_kitty_id(Zero::zero()).ok()?;
 
-            let selector = Self::random_value(&who);
+            let selector = (seed, secret).using_encoded(blake2_128);
             let mut new_dna = [0u8; 16];
 
             for i in 0..dna_1.len() {
                 new_dna[i] = (selector[i] & dna_1[i]) | (!selector[i] & dna_2[i]);
             }
 
-            Kitties::<T>::insert(kitty_id, Some(Kitty(new_dna)));
-            Owner::<T>::insert(kitty_id, Some(who.clone()));
+            Self::insert_kitty(kitty_id, owner, new_dna, Zero::zero());
 
-            KittiesCount::<T>::put(kitty_id+1u32.into());
+            Some(kitty_id)
+        }
 
-            Self::deposit_event(Event::KittyCreate(who, kitty_id));
+        /// The message an account signs to claim its starter kitty.
+        pub fn starter_claim_message(who: &T::AccountId) -> Vec<u8> {
+            (b"starter-kitty", who).encode()
+        }
+
+        /// The message a seller signs for `order`, bound to this pallet and to this chain's
+        /// genesis so the signature cannot be replayed as another payload or on another chain.
+        pub fn sell_order_message(order: &SellOrderOf<T>) -> Vec<u8> {
+            let genesis = <frame_system::Pallet<T>>::block_hash(T::BlockNumber::zero());
+            (b"kitties/sell-order", genesis, order).encode()
+        }
 
+        fn starter_claims_at(now: T::BlockNumber) -> u32 {
+            match Self::starter_claims() {
+                (block, claims) if block == now => claims,
+                _ => 0,
+            }
+        }
+
+        fn check_starter_claim(who: &T::AccountId, signature: &T::OffchainSignature) -> Result<(), Error<T>> {
+            ensure!(signature.verify(&Self::starter_claim_message(who)[..], who), Error::<T>::InvalidSignature);
+            ensure!(!Self::starter_claimed(who), Error::<T>::StarterAlreadyClaimed);
+            //只有从未发过交易、也没有kitty的新账户可以领取
+            ensure!(<frame_system::Pallet<T>>::account_nonce(who).is_zero(), Error::<T>::NotNewAccount);
+            ensure!(OwnedKitties::<T>::iter_prefix(who).next().is_none(), Error::<T>::NotNewAccount);
+            ensure!(Self::starter_kitties_claimed() < T::MaxStarterKitties::get(), Error::<T>::StarterKittiesExhausted);
+            let now = <frame_system::Pallet<T>>::block_number();
+            ensure!(Self::starter_claims_at(now) < T::StarterClaimsPerBlock::get(), Error::<T>::TooManyStarterClaims);
             Ok(())
         }
 
-        //买入kitty
-        #[pallet::weight(0)]
-        pub fn buy_kitty(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
-            let who = ensure_signed(origin.clone())?;
+        /// The id the next kitty of `collection_id` will get.
+        fn next_kitty_id(collection_id: T::CollectionId) -> Result<KittyIdOf<T>, DispatchError> {
+            let index = Self::kitties_count(collection_id);
+            ensure!(index != T::KittyIndex::max_value(), Error::<T>::KittiesCountOverflow);
+            Ok((collection_id, index))
+        }
+
+        /// Store a new kitty under an id handed out by `next_kitty_id` and notify the hooks.
+        ///
+        /// `deposit` must already be reserved from `owner`.
+        fn insert_kitty(kitty_id: KittyIdOf<T>, owner: &T::AccountId, dna: [u8; 16], deposit: BalanceOf<T>) {
+            Kitties::<T>::insert(kitty_id, KittyInfoOf::<T> {
+                dna: Kitty(dna),
+                owner: owner.clone(),
+                price: None,
+                depositor: owner.clone(),
+                deposit,
+            });
+            OwnedKitties::<T>::insert(owner, kitty_id, ());
+            KittiesCount::<T>::insert(kitty_id.0, kitty_id.1 + 1u32.into());
+            T::OnKittyCreated::on_kitty_created(owner, &kitty_id);
+        }
+
+        /// The current owner of `kitty_id`, if it exists.
+        pub fn owner(kitty_id: KittyIdOf<T>) -> Option<T::AccountId> {
+            Self::kitties(kitty_id).map(|kitty| kitty.owner)
+        }
+
+        /// The native price `kitty_id` is listed at, if it is for sale.
+        pub fn kitties_price(kitty_id: KittyIdOf<T>) -> Option<BalanceOf<T>> {
+            Self::kitties(kitty_id).and_then(|kitty| kitty.price)
+        }
+
+        fn set_price(kitty_id: KittyIdOf<T>, price: Option<BalanceOf<T>>) {
+            Kitties::<T>::mutate(kitty_id, |kitty| if let Some(kitty) = kitty {
+                kitty.price = price;
+                if price.is_some() {
+                    Listings::<T>::insert(kitty_id, ());
+                } else {
+                    Listings::<T>::remove(kitty_id);
+                }
+            });
+        }
+
+        /// Give `kitty_id` to `owner`, withdrawing any listing.
+        fn set_owner(kitty_id: KittyIdOf<T>, owner: &T::AccountId) {
+            Kitties::<T>::mutate(kitty_id, |kitty| if let Some(kitty) = kitty {
+                OwnedKitties::<T>::remove(&kitty.owner, kitty_id);
+                OwnedKitties::<T>::insert(owner, kitty_id, ());
+                kitty.owner = owner.clone();
+                kitty.price = None;
+            });
+            Listings::<T>::remove(kitty_id);
+            KittiesAssetPrice::<T>::remove(kitty_id);
+        }
+
+        /// Hand `kitty_id` from `from` to `to` without payment, withdrawing any listing.
+        ///
+        /// Used by `transfer` and by other pallets that move kitties on their owners' behalf.
+        pub fn do_transfer(from: &T::AccountId, to: &T::AccountId, kitty_id: KittyIdOf<T>) -> DispatchResult {
+            ensure!(Some(from.clone()) == Self::owner(kitty_id), Error::<T>::NotOwner);
+            ensure!(!Self::is_locked(kitty_id), Error::<T>::KittyLocked);
+
+            Self::set_owner(kitty_id, to);
+            Self::record_history(kitty_id, from, to, None);
+            T::OnKittyTransferred::on_kitty_transferred(from, to, &kitty_id);
+
+            Self::deposit_event(Event::KittyTransfer(from.clone(), to.clone(), kitty_id));
+
+            Ok(())
+        }
 
-            
+        /// Remove `kitty_id` and everything kept about it, returning its deposit to whoever paid it.
+        pub fn do_burn(owner: &T::AccountId, kitty_id: KittyIdOf<T>) -> DispatchResult {
+            let kitty = Self::kitties(kitty_id).ok_or(Error::<T>::InvalidKittyIndex)?;
+            ensure!(kitty.owner == *owner, Error::<T>::NotOwner);
+            ensure!(!Self::is_locked(kitty_id), Error::<T>::KittyLocked);
+
+            Kitties::<T>::remove(kitty_id);
+            OwnedKitties::<T>::remove(owner, kitty_id);
+            Listings::<T>::remove(kitty_id);
+            KittiesAssetPrice::<T>::remove(kitty_id);
+            KittyHistory::<T>::remove(kitty_id);
+            T::Currency::unreserve(&kitty.depositor, kitty.deposit);
+            T::OnKittyBurned::on_kitty_burned(owner, &kitty_id);
+
+            Self::deposit_event(Event::KittyBurn(owner.clone(), kitty_id));
+
+            Ok(())
+        }
+
+        /// Freeze a kitty so it cannot be transferred or sold until `unlock` is called.
+        pub fn lock(kitty_id: KittyIdOf<T>) -> DispatchResult {
             ensure!(Kitties::<T>::contains_key(kitty_id), Error::<T>::InvalidKittyIndex);
-            let from = Owner::<T>::get(kitty_id).unwrap();
-            ensure!(who.clone() != from, Error::<T>::FromSameTo);
+            ensure!(!Self::is_locked(kitty_id), Error::<T>::KittyLocked);
+            LockedKitties::<T>::insert(kitty_id, true);
+            Ok(())
+        }
 
-            let price = Self::kitties_price(kitty_id).ok_or(Error::<T>::NotKittySale)?;
+        pub fn unlock(kitty_id: KittyIdOf<T>) {
+            LockedKitties::<T>::remove(kitty_id);
+        }
+
+        /// Reserve a fresh deposit from `buyer` for `kitty_id` and release the one held for it so far,
+        /// which may be nothing for a starter or bred kitty and may belong to an earlier owner.
+        fn move_deposit(kitty_id: KittyIdOf<T>, buyer: &T::AccountId) -> DispatchResult {
+            let deposit = T::KittyDepositBase::get();
+            T::Currency::reserve(buyer, deposit).map_err(|_| Error::<T>::BalanceLitter)?;
+            Kitties::<T>::try_mutate(kitty_id, |kitty| {
+                let kitty = kitty.as_mut().ok_or(Error::<T>::InvalidKittyIndex)?;
+                T::Currency::unreserve(&kitty.depositor, kitty.deposit);
+                kitty.depositor = buyer.clone();
+                kitty.deposit = deposit;
+                Ok(())
+            })
+        }
+
+        /// Settle a sale of `kitty_id` from `seller` to `buyer` at `price`, moving the deposit with it.
+        fn do_buy(
+            buyer: &T::AccountId,
+            seller: &T::AccountId,
+            kitty_id: KittyIdOf<T>,
+            price: BalanceOf<T>,
+        ) -> DispatchResult {
+            ensure!(!Self::is_locked(kitty_id), Error::<T>::KittyLocked);
             //判断账户中的balance大于等于交易费用
-            let reserve = T::KittyDepositBase::get();
-           
-            T::Currency::reserve(&who, reserve).map_err(|_| Error::<T>::BalanceLitter)?;
-            T::Currency::unreserve(&from, reserve); 
+            Self::move_deposit(kitty_id, buyer)?;
+            let proceeds = Self::pay_royalty(buyer, seller, kitty_id, price)?;
             T::Currency::transfer(
-                &who,
-                &from,
-                price,
+                buyer,
+                seller,
+                proceeds,
                 ExistenceRequirement::KeepAlive,
             )?;
-            KittiesPrice::<T>::remove(kitty_id);
-            Owner::<T>::insert(kitty_id, Some(who.clone()));
+            Self::set_owner(kitty_id, buyer);
+            Self::record_history(kitty_id, seller, buyer, Some(SalePrice::Native(price)));
+            T::OnKittyTransferred::on_kitty_transferred(seller, buyer, &kitty_id);
+            Self::deposit_event(Event::KittySold(seller.clone(), buyer.clone(), kitty_id, SalePrice::Native(price)));
+
+            Ok(())
+        }
+
+        /// Like `do_buy`, but the price and royalty are settled in `asset_id` through `T::Assets`.
+        fn do_buy_with_asset(
+            buyer: &T::AccountId,
+            seller: &T::AccountId,
+            kitty_id: KittyIdOf<T>,
+            asset_id: AssetIdOf<T>,
+            price: AssetBalanceOf<T>,
+        ) -> DispatchResult {
+            use fungibles::Transfer;
 
-            Self::deposit_event(Event::KittyTransfer(from, who, kitty_id));
+            ensure!(!Self::is_locked(kitty_id), Error::<T>::KittyLocked);
+            Self::move_deposit(kitty_id, buyer)?;
+            let mut proceeds = price;
+            if let Some((creator, royalty)) = Self::royalty_of(buyer, seller, kitty_id) {
+                let royalty = royalty.mul_floor(price);
+                if !royalty.is_zero() {
+                    T::Assets::transfer(asset_id, buyer, &creator, royalty, true)?;
+                }
+                proceeds = price - royalty;
+            }
+            T::Assets::transfer(asset_id, buyer, seller, proceeds, true)?;
+            Self::set_owner(kitty_id, buyer);
+            Self::record_history(kitty_id, seller, buyer, Some(SalePrice::Asset(asset_id, price)));
+            T::OnKittyTransferred::on_kitty_transferred(seller, buyer, &kitty_id);
+            Self::deposit_event(Event::KittySold(seller.clone(), buyer.clone(), kitty_id, SalePrice::Asset(asset_id, price)));
 
             Ok(())
         }
 
-        //卖出kitty
-        #[pallet::weight(0)]
-        pub fn sell_kitty(origin: OriginFor<T>, kitty_id: T::KittyIndex, amount: Option<BalanceOf<T>>) -> DispatchResult {
-            let who = ensure_signed(origin)?;
+        /// Append a change of ownership to the kitty's history, pruning the oldest entry when full.
+        fn record_history(
+            kitty_id: KittyIdOf<T>,
+            from: &T::AccountId,
+            to: &T::AccountId,
+            price: Option<SalePriceOf<T>>,
+        ) {
+            let max = T::MaxHistoryLength::get() as usize;
+            if max == 0 {
+                return;
+            }
+            KittyHistory::<T>::mutate(kitty_id, |history| {
+                while history.len() >= max {
+                    history.remove(0);
+                }
+                history.push(HistoryEntry {
+                    block_number: <frame_system::Pallet<T>>::block_number(),
+                    from: from.clone(),
+                    to: to.clone(),
+                    price,
+                });
+            });
+        }
+
+        /// The provenance of a kitty, oldest entry first.
+        pub fn history_of(kitty_id: KittyIdOf<T>) -> Vec<HistoryEntryOf<T>> {
+            Self::kitty_history(kitty_id)
+        }
 
-            ensure!(Some(who.clone()) == Owner::<T>::get(kitty_id), Error::<T>::FromSameTo);
+        /// Up to `limit` kitties held by `owner` with an id above `start_after`, ordered by id.
+        pub fn kitties_of(
+            owner: &T::AccountId,
+            start_after: Option<KittyIdOf<T>>,
+            limit: u32,
+        ) -> Vec<(KittyIdOf<T>, KittyInfoOf<T>)> {
+            Self::page(OwnedKitties::<T>::iter_prefix(owner).map(|(kitty_id, _)| kitty_id), start_after, limit)
+        }
 
-            KittiesPrice::<T>::mutate_exists(kitty_id, |p| *p = Some(amount));
-            Self::deposit_event(Event::KittySale(who, kitty_id, amount));
+        /// Up to `limit` kitties listed for a native price with an id above `start_after`, ordered by id.
+        pub fn listings(start_after: Option<KittyIdOf<T>>, limit: u32) -> Vec<(KittyIdOf<T>, KittyInfoOf<T>)> {
+            Self::page(Listings::<T>::iter().map(|(kitty_id, _)| kitty_id), start_after, limit)
+        }
 
-            Ok(())
+        /// Sort `ids` and read the first `limit` kitties after the cursor. Only the ids are walked,
+        /// the kitties themselves are read for the returned page alone.
+        fn page(
+            ids: impl Iterator<Item = KittyIdOf<T>>,
+            start_after: Option<KittyIdOf<T>>,
+            limit: u32,
+        ) -> Vec<(KittyIdOf<T>, KittyInfoOf<T>)> {
+            let mut ids: Vec<_> = ids.filter(|kitty_id| start_after.map_or(true, |after| *kitty_id > after)).collect();
+            ids.sort();
+            ids.into_iter()
+                .take(limit as usize)
+                .filter_map(|kitty_id| Some((kitty_id, Self::kitties(kitty_id)?)))
+                .collect()
         }
 
-    }
-    
-    impl<T: Config> Pallet<T> {
+        /// The traits expressed by the DNA of `kitty_id`.
+        pub fn phenotype(kitty_id: KittyIdOf<T>) -> Option<Phenotype> {
+            Self::kitties(kitty_id).map(|kitty| kitty.dna.phenotype())
+        }
+
+        /// The collection creator owed a royalty on a sale between `buyer` and `seller`, if any.
+        fn royalty_of(
+            buyer: &T::AccountId,
+            seller: &T::AccountId,
+            kitty_id: KittyIdOf<T>,
+        ) -> Option<(T::AccountId, Permill)> {
+            let collection = Self::collections(kitty_id.0)?;
+            if &collection.owner == seller || &collection.owner == buyer {
+                return None;
+            }
+            Some((collection.owner, collection.royalty))
+        }
+
+        /// Pay the collection creator's royalty out of `price`, returning what is left for the seller.
+        fn pay_royalty(
+            buyer: &T::AccountId,
+            seller: &T::AccountId,
+            kitty_id: KittyIdOf<T>,
+            price: BalanceOf<T>,
+        ) -> Result<BalanceOf<T>, DispatchError> {
+            let (creator, royalty) = match Self::royalty_of(buyer, seller, kitty_id) {
+                Some(royalty) => royalty,
+                None => return Ok(price),
+            };
+            let royalty = royalty.mul_floor(price);
+            if !royalty.is_zero() {
+                T::Currency::transfer(buyer, &creator, royalty, ExistenceRequirement::KeepAlive)?;
+            }
+            Ok(price - royalty)
+        }
+
+
         pub fn random_value(sender: &T::AccountId)-> [u8; 16] {
             let payload = (
                 T::Randomness::random_seed(),
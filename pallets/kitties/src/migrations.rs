@@ -0,0 +1,136 @@
+//! Storage migrations for the kitties pallet.
+//!
+//! Each migration reads and writes the raw storage of the layout it migrates from, so that
+//! later changes to the pallet's storage items don't break it.
+
+use crate::{Config, Pallet};
+use frame_support::traits::PalletInfoAccess;
+
+fn pallet_prefix<T: Config>() -> &'static [u8] {
+    <Pallet<T> as PalletInfoAccess>::name().as_bytes()
+}
+
+pub mod v2 {
+    //! Fold the `Kitties`, `Owner` and `KittiesPrice` maps into a single `Kitties` map of `KittyInfo`.
+    //!
+    //! Kitties are now keyed by `(CollectionId, KittyIndex)`: every existing kitty keeps its index
+    //! in the common collection `0`, whose counter takes over from the global `KittiesCount`.
+    //! Each kitty is indexed under its owner, and listed kitties are kept in `Listings`.
+    //!
+    //! Starter and bred kitties never reserved a deposit and can't be told apart from the others
+    //! in storage, so each kitty records its owner as the depositor and at most what is still
+    //! reserved from them, shared out in kitty id order.
+
+    use super::pallet_prefix;
+    use crate::{
+        BalanceOf, Config, Kitties, KittiesCount, Kitty, KittyInfoOf, Listings, OwnedKitties, Releases,
+        StorageVersion,
+    };
+    use codec::Encode;
+    use frame_support::{
+        Blake2_128Concat, StorageHasher,
+        storage::migration::{get_storage_value, remove_storage_prefix, storage_key_iter, take_storage_item, take_storage_value},
+        traits::{Get, ReservableCurrency},
+        weights::Weight,
+    };
+    use sp_runtime::traits::Zero;
+    use sp_std::{cmp, collections::btree_map::BTreeMap, prelude::*};
+
+    /// Check the old maps are consistent before migrating: every kitty has an owner.
+    pub fn pre_migrate<T: Config>() -> Result<(), &'static str> {
+        let prefix = pallet_prefix::<T>();
+        frame_support::ensure!(StorageVersion::<T>::get() == Releases::V1, "kitties storage is not at V1");
+
+        for (kitty_id, _) in storage_key_iter::<T::KittyIndex, Option<Kitty>, Blake2_128Concat>(prefix, b"Kitties") {
+            let key = kitty_id.using_encoded(Blake2_128Concat::hash);
+            let owner = get_storage_value::<Option<T::AccountId>>(prefix, b"Owner", &key).flatten();
+            frame_support::ensure!(owner.is_some(), "kitty without an owner");
+        }
+
+        Ok(())
+    }
+
+    pub fn migrate<T: Config>() -> Weight {
+        let prefix = pallet_prefix::<T>();
+        let kitties: Vec<(T::KittyIndex, Option<Kitty>)> =
+            storage_key_iter::<T::KittyIndex, Option<Kitty>, Blake2_128Concat>(prefix, b"Kitties").drain().collect();
+        let translated = kitties.len() as u64;
+
+        let count = take_storage_value::<T::KittyIndex>(prefix, b"KittiesCount", &[]).unwrap_or_default();
+        KittiesCount::<T>::insert(T::CollectionId::zero(), count);
+
+        let mut reserved: BTreeMap<T::AccountId, BalanceOf<T>> = BTreeMap::new();
+        let mut listed = 0u64;
+        for (index, kitty) in kitties {
+            let owner = take_storage_item::<_, Option<T::AccountId>, Blake2_128Concat>(prefix, b"Owner", index)
+                .flatten();
+            let price = take_storage_item::<_, Option<BalanceOf<T>>, Blake2_128Concat>(prefix, b"KittiesPrice", index)
+                .flatten();
+            let (dna, owner) = match (kitty, owner) {
+                (Some(dna), Some(owner)) => (dna, owner),
+                _ => continue,
+            };
+
+            let left = reserved.entry(owner.clone()).or_insert_with(|| T::Currency::reserved_balance(&owner));
+            let deposit = cmp::min(T::KittyDepositBase::get(), *left);
+            *left -= deposit;
+
+            let kitty_id = (T::CollectionId::zero(), index);
+            OwnedKitties::<T>::insert(&owner, kitty_id, ());
+            if price.is_some() {
+                listed += 1;
+                Listings::<T>::insert(kitty_id, ());
+            }
+            Kitties::<T>::insert(kitty_id, KittyInfoOf::<T> { dna, owner: owner.clone(), price, depositor: owner, deposit });
+        }
+        // Prices and owners of kitties that no longer exist.
+        remove_storage_prefix(prefix, b"Owner", &[]);
+        remove_storage_prefix(prefix, b"KittiesPrice", &[]);
+
+        StorageVersion::<T>::put(Releases::V2);
+
+        T::DbWeight::get().reads_writes(4 * translated + 2, 4 * translated + listed + 4)
+    }
+
+    /// Check every kitty survived the migration, is indexed under its owner, no account is
+    /// recorded as having more reserved for its kitties than it has reserved, and nothing is
+    /// left in the old maps.
+    pub fn post_migrate<T: Config>() -> Result<(), &'static str> {
+        let prefix = pallet_prefix::<T>();
+        frame_support::ensure!(StorageVersion::<T>::get() == Releases::V2, "kitties storage is not at V2");
+        frame_support::ensure!(
+            get_storage_value::<T::KittyIndex>(prefix, b"KittiesCount", &[]).is_none(),
+            "global KittiesCount not cleared"
+        );
+
+        let mut deposits: BTreeMap<T::AccountId, BalanceOf<T>> = BTreeMap::new();
+        for ((collection_id, index), kitty) in Kitties::<T>::iter() {
+            frame_support::ensure!(index < KittiesCount::<T>::get(collection_id), "kitty index beyond KittiesCount");
+            frame_support::ensure!(
+                OwnedKitties::<T>::contains_key(&kitty.owner, (collection_id, index)),
+                "kitty missing from OwnedKitties"
+            );
+            frame_support::ensure!(
+                kitty.price.is_some() == Listings::<T>::contains_key((collection_id, index)),
+                "Listings out of step with prices"
+            );
+            *deposits.entry(kitty.depositor).or_default() += kitty.deposit;
+        }
+        for (depositor, deposit) in deposits {
+            frame_support::ensure!(
+                deposit <= T::Currency::reserved_balance(&depositor),
+                "kitty deposits exceed the depositor's reserved balance"
+            );
+        }
+        frame_support::ensure!(
+            storage_key_iter::<T::KittyIndex, Option<T::AccountId>, Blake2_128Concat>(prefix, b"Owner").next().is_none(),
+            "Owner map not cleared"
+        );
+        frame_support::ensure!(
+            storage_key_iter::<T::KittyIndex, Option<BalanceOf<T>>, Blake2_128Concat>(prefix, b"KittiesPrice").next().is_none(),
+            "KittiesPrice map not cleared"
+        );
+
+        Ok(())
+    }
+}
@@ -1,8 +1,10 @@
 use crate as pallet_kitties;
+use crate::traits::{OnKittyCreated, OnKittyTransferred, OnKittyBurned};
+use std::cell::RefCell;
 use sp_core::H256;
-use frame_support::parameter_types;
+use frame_support::{parameter_types, traits::Hooks};
 use sp_runtime::{
-	traits::{BlakeTwo256, IdentityLookup}, testing::Header,
+	traits::{BlakeTwo256, IdentityLookup}, testing::{Header, TestSignature, UintAuthorityId},
 };
 use frame_system as system;
 
@@ -25,7 +27,8 @@ frame_support::construct_runtime!(
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
 		RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Pallet, Storage},
-		KittyModule: pallet_kitties::{Pallet, Call, Storage, Event<T>},
+		Assets: pallet_assets::{Pallet, Call, Storage, Event<T>},
+		KittyModule: pallet_kitties::{Pallet, Call, Storage, Event<T>, Config<T>, ValidateUnsigned},
 	}
 );
 
@@ -66,6 +69,15 @@ parameter_types! {
 	pub const ExistentialDeposit: u128 = 500;
 	pub const MaxLocks: u32 = 50;
 	pub const KittyDepositBase: u32 = 1_000;
+	pub const CollectionDeposit: u32 = 10_000;
+	pub const MaxMetadataLength: u32 = 32;
+	pub const MaxHistoryLength: u32 = 2;
+	pub const BreedRevealDelay: u64 = 3;
+	pub const BreedRevealWindow: u64 = 5;
+	pub const BreedDeposit: u32 = 100;
+	pub const MaxBreedsPerBlock: u32 = 2;
+	pub const StarterClaimsPerBlock: u32 = 2;
+	pub const MaxStarterKitties: u32 = 3;
 }
 
 impl pallet_balances::Config for Test {
@@ -82,12 +94,82 @@ impl pallet_balances::Config for Test {
 	type WeightInfo = pallet_balances::weights::SubstrateWeight<Test>;
 }
 
+parameter_types! {
+	pub const AssetDeposit: u128 = 1;
+	pub const ApprovalDeposit: u128 = 1;
+	pub const StringLimit: u32 = 50;
+	pub const MetadataDepositBase: u128 = 1;
+	pub const MetadataDepositPerByte: u128 = 1;
+}
+
+impl pallet_assets::Config for Test {
+	type Event = Event;
+	type Balance = Balance;
+	type AssetId = u32;
+	type Currency = Balances;
+	type ForceOrigin = frame_system::EnsureRoot<u64>;
+	type AssetDeposit = AssetDeposit;
+	type MetadataDepositBase = MetadataDepositBase;
+	type MetadataDepositPerByte = MetadataDepositPerByte;
+	type ApprovalDeposit = ApprovalDeposit;
+	type StringLimit = StringLimit;
+	type Freezer = ();
+	type Extra = ();
+	type WeightInfo = ();
+}
+
+/// Identifies a kitty by collection and index.
+pub type KittyId = (u32, Index);
+
+thread_local! {
+	pub static CREATED: RefCell<Vec<(u64, KittyId)>> = RefCell::new(vec![]);
+	pub static TRANSFERRED: RefCell<Vec<(u64, u64, KittyId)>> = RefCell::new(vec![]);
+	pub static BURNED: RefCell<Vec<(u64, KittyId)>> = RefCell::new(vec![]);
+}
+
+/// Records every kitty hook call so tests can inspect them.
+pub struct RecordKittyHooks;
+
+impl OnKittyCreated<u64, KittyId> for RecordKittyHooks {
+	fn on_kitty_created(owner: &u64, kitty_id: &KittyId) {
+		CREATED.with(|c| c.borrow_mut().push((*owner, *kitty_id)));
+	}
+}
+
+impl OnKittyTransferred<u64, KittyId> for RecordKittyHooks {
+	fn on_kitty_transferred(from: &u64, to: &u64, kitty_id: &KittyId) {
+		TRANSFERRED.with(|t| t.borrow_mut().push((*from, *to, *kitty_id)));
+	}
+}
+
+impl OnKittyBurned<u64, KittyId> for RecordKittyHooks {
+	fn on_kitty_burned(owner: &u64, kitty_id: &KittyId) {
+		BURNED.with(|b| b.borrow_mut().push((*owner, *kitty_id)));
+	}
+}
+
 impl pallet_kitties::Config for Test {
 	type Event = Event;
 	type Randomness = RandomnessCollectiveFlip;
 	type KittyIndex = Index;
 	type Currency = Balances;
 	type KittyDepositBase = KittyDepositBase;
+	type CollectionId = u32;
+	type CollectionDeposit = CollectionDeposit;
+	type MaxMetadataLength = MaxMetadataLength;
+	type OffchainSignature = TestSignature;
+	type OffchainPublic = UintAuthorityId;
+	type Assets = Assets;
+	type OnKittyCreated = RecordKittyHooks;
+	type OnKittyTransferred = RecordKittyHooks;
+	type OnKittyBurned = RecordKittyHooks;
+	type MaxHistoryLength = MaxHistoryLength;
+	type BreedRevealDelay = BreedRevealDelay;
+	type BreedRevealWindow = BreedRevealWindow;
+	type BreedDeposit = BreedDeposit;
+	type MaxBreedsPerBlock = MaxBreedsPerBlock;
+	type StarterClaimsPerBlock = StarterClaimsPerBlock;
+	type MaxStarterKitties = MaxStarterKitties;
 }
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
@@ -100,3 +182,10 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
 	ext.execute_with(|| System::set_block_number(1)); //设置初始块高度
 	ext
 }
+
+pub fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		System::set_block_number(System::block_number() + 1);
+		KittyModule::on_initialize(System::block_number());
+	}
+}
@@ -0,0 +1,193 @@
+//! A mock runtime with kitties, balances, assets and randomness, for the tests of pallets built
+//! on top of this one.
+//!
+//! A pallet's `mock.rs` calls [`kitty_test_runtime!`] with its own pallets and then only
+//! implements their `Config`:
+//!
+//! ```ignore
+//! use crate as pallet_loans;
+//!
+//! pallet_kitties::kitty_test_runtime! {
+//! 	LoanModule: pallet_loans::{Pallet, Call, Storage, Event<T>},
+//! }
+//!
+//! impl pallet_loans::Config for Test {
+//! 	type Event = Event;
+//! }
+//! ```
+//!
+//! Pallets that listen to kitty events pass themselves as the kitty hooks, and may give every test
+//! account a different balance:
+//!
+//! ```ignore
+//! pallet_kitties::kitty_test_runtime! {
+//! 	hooks = DiscountModule, balance = 1 << 40;
+//! 	DiscountModule: pallet_discounts::{Pallet, Call, Storage, Event<T>},
+//! }
+//! ```
+//!
+//! The calling crate needs `pallet-kitties`, `frame-support`, `frame-system`, `sp-core`, `sp-io`,
+//! `sp-runtime`, `pallet-balances`, `pallet-assets` and `pallet-randomness-collective-flip` among
+//! its (dev-)dependencies.
+
+/// Declare the `Test` runtime, `new_test_ext` and `create_kitties`. See the module docs.
+#[macro_export]
+macro_rules! kitty_test_runtime {
+	(hooks = $hooks:ty, balance = $balance:expr; $($pallets:tt)*) => {
+		type UncheckedExtrinsic = ::frame_system::mocking::MockUncheckedExtrinsic<Test>;
+		type Block = ::frame_system::mocking::MockBlock<Test>;
+
+		/// Balance of an account.
+		pub type Balance = u128;
+
+		/// Index of a transaction in the chain.
+		pub type Index = u32;
+
+		// Configure a mock runtime to test the pallet.
+		::frame_support::construct_runtime!(
+			pub enum Test where
+				Block = Block,
+				NodeBlock = Block,
+				UncheckedExtrinsic = UncheckedExtrinsic,
+			{
+				System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+				Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+				RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Pallet, Storage},
+				Assets: pallet_assets::{Pallet, Call, Storage, Event<T>},
+				KittyModule: pallet_kitties::{Pallet, Call, Storage, Event<T>},
+				$($pallets)*
+			}
+		);
+
+		impl ::pallet_randomness_collective_flip::Config for Test {}
+
+		::frame_support::parameter_types! {
+			pub const BlockHashCount: u64 = 250;
+			pub const SS58Prefix: u8 = 42;
+		}
+
+		impl ::frame_system::Config for Test {
+			type BaseCallFilter = ();
+			type BlockWeights = ();
+			type BlockLength = ();
+			type DbWeight = ();
+			type Origin = Origin;
+			type Call = Call;
+			type Index = u64;
+			type BlockNumber = u64;
+			type Hash = ::sp_core::H256;
+			type Hashing = ::sp_runtime::traits::BlakeTwo256;
+			type AccountId = u64;
+			type Lookup = ::sp_runtime::traits::IdentityLookup<Self::AccountId>;
+			type Header = ::sp_runtime::testing::Header;
+			type Event = Event;
+			type BlockHashCount = BlockHashCount;
+			type Version = ();
+			type PalletInfo = PalletInfo;
+			type AccountData = ::pallet_balances::AccountData<Balance>;
+			type OnNewAccount = ();
+			type OnKilledAccount = ();
+			type SystemWeightInfo = ();
+			type SS58Prefix = SS58Prefix;
+			type OnSetCode = ();
+		}
+
+		::frame_support::parameter_types! {
+			pub const ExistentialDeposit: u128 = 500;
+			pub const MaxLocks: u32 = 50;
+			pub const KittyDepositBase: u32 = 1_000;
+			pub const CollectionDeposit: u32 = 10_000;
+			pub const MaxMetadataLength: u32 = 32;
+			pub const MaxHistoryLength: u32 = 2;
+			pub const BreedRevealDelay: u64 = 3;
+			pub const BreedRevealWindow: u64 = 5;
+			pub const BreedDeposit: u32 = 100;
+			pub const MaxBreedsPerBlock: u32 = 2;
+			pub const StarterClaimsPerBlock: u32 = 2;
+			pub const MaxStarterKitties: u32 = 3;
+		}
+
+		impl ::pallet_balances::Config for Test {
+			type MaxLocks = MaxLocks;
+			type MaxReserves = ();
+			type ReserveIdentifier = [u8; 8];
+			type Balance = Balance;
+			type Event = Event;
+			type DustRemoval = ();
+			type ExistentialDeposit = ExistentialDeposit;
+			type AccountStore = System;
+			type WeightInfo = ::pallet_balances::weights::SubstrateWeight<Test>;
+		}
+
+		::frame_support::parameter_types! {
+			pub const AssetDeposit: u128 = 1;
+			pub const ApprovalDeposit: u128 = 1;
+			pub const StringLimit: u32 = 50;
+			pub const MetadataDepositBase: u128 = 1;
+			pub const MetadataDepositPerByte: u128 = 1;
+		}
+
+		impl ::pallet_assets::Config for Test {
+			type Event = Event;
+			type Balance = Balance;
+			type AssetId = u32;
+			type Currency = Balances;
+			type ForceOrigin = ::frame_system::EnsureRoot<u64>;
+			type AssetDeposit = AssetDeposit;
+			type MetadataDepositBase = MetadataDepositBase;
+			type MetadataDepositPerByte = MetadataDepositPerByte;
+			type ApprovalDeposit = ApprovalDeposit;
+			type StringLimit = StringLimit;
+			type Freezer = ();
+			type Extra = ();
+			type WeightInfo = ();
+		}
+
+		impl $crate::Config for Test {
+			type Event = Event;
+			type Randomness = RandomnessCollectiveFlip;
+			type KittyIndex = Index;
+			type Currency = Balances;
+			type KittyDepositBase = KittyDepositBase;
+			type CollectionId = u32;
+			type CollectionDeposit = CollectionDeposit;
+			type MaxMetadataLength = MaxMetadataLength;
+			type OffchainSignature = ::sp_runtime::testing::TestSignature;
+			type OffchainPublic = ::sp_runtime::testing::UintAuthorityId;
+			type Assets = Assets;
+			type OnKittyCreated = $hooks;
+			type OnKittyTransferred = $hooks;
+			type OnKittyBurned = $hooks;
+			type MaxHistoryLength = MaxHistoryLength;
+			type BreedRevealDelay = BreedRevealDelay;
+			type BreedRevealWindow = BreedRevealWindow;
+			type BreedDeposit = BreedDeposit;
+			type MaxBreedsPerBlock = MaxBreedsPerBlock;
+			type StarterClaimsPerBlock = StarterClaimsPerBlock;
+			type MaxStarterKitties = MaxStarterKitties;
+		}
+
+		// Build genesis storage according to the mock runtime.
+		pub fn new_test_ext() -> ::sp_io::TestExternalities {
+			let mut t = ::frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+			::pallet_balances::GenesisConfig::<Test>{
+				balances: vec![(0, $balance), (1, $balance), (2, $balance)],
+			}.assimilate_storage(&mut t).unwrap();
+
+			let mut ext = ::sp_io::TestExternalities::new(t);
+			ext.execute_with(|| System::set_block_number(1)); //设置初始块高度
+			ext
+		}
+
+		/// Create one kitty for each of `owners`, in order, so they get ids `(0, 0)`, `(0, 1)`, ...
+		#[allow(dead_code)]
+		pub fn create_kitties(owners: &[u64]) {
+			for owner in owners {
+				::frame_support::assert_ok!(KittyModule::create(Origin::signed(*owner)));
+			}
+		}
+	};
+	($($pallets:tt)*) => {
+		$crate::kitty_test_runtime! { hooks = (), balance = 100_000_000; $($pallets)* }
+	};
+}
@@ -1,5 +1,12 @@
 use crate::{Error, Event, mock::*};
-use frame_support::{assert_ok, assert_noop};
+use frame_support::{assert_ok, assert_noop, Blake2_128Concat, StorageHasher};
+use frame_support::{
+	storage::migration::put_storage_value,
+	traits::{Hooks, PalletInfoAccess, ReservableCurrency},
+};
+use sp_core::H256;
+use sp_runtime::{Permill, testing::TestSignature, traits::{BlakeTwo256, Hash}};
+use codec::Encode;
 use super::*;
 
 //测试创建kitty 
@@ -7,14 +14,41 @@ use super::*;
 fn create_works() {
 	new_test_ext().execute_with(|| {
 		assert_ok!(KittyModule::create(Origin::signed(1)));
-		System::assert_has_event(mock::Event::KittyModule(Event::KittyCreate(1,1)));
+		System::assert_has_event(mock::Event::KittyModule(Event::KittyCreate(1, (0, 0))));
+	});
+}
+
+#[test]
+fn genesis_kitties_are_minted() {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test>{
+		balances: vec![(1, 100_000), (2, 100_000)],
+	}.assimilate_storage(&mut t).unwrap();
+	crate::GenesisConfig::<Test>{
+		kitties: vec![(1, [1u8; 16], None), (2, [2u8; 16], Some(500))],
+	}.assimilate_storage(&mut t).unwrap();
+
+	sp_io::TestExternalities::new(t).execute_with(|| {
+		assert_eq!(KittyModule::kitties_count(0), 2);
+		assert_eq!(KittyModule::kitties((0, 1)).unwrap().dna.0, [2u8; 16]);
+		assert_eq!(KittyModule::owner((0, 0)), Some(1));
+		assert_eq!(KittyModule::owner((0, 1)), Some(2));
+		assert_eq!(KittyModule::kitties_price((0, 0)), None);
+		assert_eq!(KittyModule::kitties_price((0, 1)), Some(500));
+		assert_eq!(KittyModule::storage_version(), Releases::V2);
+		assert_eq!(KittyModule::kitties((0, 1)).unwrap().deposit, 1_000);
+		assert_eq!(Balances::reserved_balance(1), 1_000);
+		assert_eq!(Balances::reserved_balance(2), 1_000);
+		//创世kitty同样经过索引和钩子
+		assert_eq!(KittyModule::listings(None, 10).into_iter().map(|(id, _)| id).collect::<Vec<_>>(), vec![(0, 1)]);
+		CREATED.with(|c| assert_eq!(*c.borrow(), vec![(1, (0, 0)), (2, (0, 1))]));
 	});
 }
 
 #[test]
 fn create_failed_when_index_max() {
 	new_test_ext().execute_with(|| {
-		KittiesCount::<Test>::put(u32::max_value());
+		KittiesCount::<Test>::insert(0, u32::max_value());
 		assert_noop!(
 			KittyModule::create(Origin::signed(1)),
 			Error::<Test>::KittiesCountOverflow
@@ -33,7 +67,21 @@ fn create_not_enough_balance() {
 fn transfer_work() {
 	new_test_ext().execute_with(|| {
 		assert_ok!(KittyModule::create(Origin::signed(1)));
-		assert_ok!(KittyModule::transfer(Origin::signed(1), 2, 1));
+		assert_ok!(KittyModule::transfer(Origin::signed(1), 2, (0, 0)));
+	})
+}
+
+#[test]
+fn transfer_withdraws_listing() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		assert_ok!(KittyModule::sell_kitty(Origin::signed(1), (0, 0), Some(100)));
+		assert_ok!(KittyModule::transfer(Origin::signed(1), 2, (0, 0)));
+
+		//转让之后原来的挂单不再有效
+		assert_eq!(KittyModule::kitties((0, 0)).unwrap().price, None);
+		assert!(KittyModule::listings(None, 10).is_empty());
+		assert_noop!(KittyModule::buy_kitty(Origin::signed(1), (0, 0)), Error::<Test>::NotKittySale);
 	})
 }
 
@@ -41,16 +89,127 @@ fn transfer_work() {
 fn transfer_failed_not_owner() {
 	new_test_ext().execute_with(|| {
 		assert_ok!(KittyModule::create(Origin::signed(1)));
-		assert_noop!(KittyModule::transfer(Origin::signed(2), 3, 1), Error::<Test>::NotOwner);
+		assert_noop!(KittyModule::transfer(Origin::signed(2), 3, (0, 0)), Error::<Test>::NotOwner);
 	})
 }
 
+#[test]
+fn burn_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		assert_ok!(KittyModule::sell_kitty(Origin::signed(1), (0, 0), Some(100)));
+		assert_ok!(KittyModule::transfer(Origin::signed(1), 2, (0, 0)));
+
+		//押金退还给创建者
+		assert_ok!(KittyModule::burn(Origin::signed(2), (0, 0)));
+		System::assert_has_event(mock::Event::KittyModule(Event::KittyBurn(2, (0, 0))));
+		assert_eq!(KittyModule::kitties((0, 0)), None);
+		assert!(KittyModule::kitties_of(&2, None, 10).is_empty());
+		assert!(KittyModule::history_of((0, 0)).is_empty());
+		assert_eq!(Balances::reserved_balance(1), 0);
+
+		//编号不会复用
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		assert_eq!(KittyModule::owner((0, 1)), Some(1));
+	});
+}
+
+#[test]
+fn burn_failed() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(KittyModule::burn(Origin::signed(1), (0, 0)), Error::<Test>::InvalidKittyIndex);
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		assert_noop!(KittyModule::burn(Origin::signed(2), (0, 0)), Error::<Test>::NotOwner);
+		assert_ok!(KittyModule::lock((0, 0)));
+		assert_noop!(KittyModule::burn(Origin::signed(1), (0, 0)), Error::<Test>::KittyLocked);
+	});
+}
+
+fn secret_hash(secret: [u8; 32]) -> H256 {
+	BlakeTwo256::hash(&secret)
+}
+
 #[test]
 fn breed_work() {
 	new_test_ext().execute_with(|| {
 		assert_ok!(KittyModule::create(Origin::signed(1)));
 		assert_ok!(KittyModule::create(Origin::signed(1)));
-		assert_ok!(KittyModule::breed(Origin::signed(1), 1, 2));
+		assert_ok!(KittyModule::breed(Origin::signed(1), (0, 0), (0, 1), secret_hash([7; 32])));
+		assert_eq!(Balances::reserved_balance(1), 2_000 + 100);
+	})
+}
+
+#[test]
+fn breed_reveals_child_later() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		assert_ok!(KittyModule::breed(Origin::signed(2), (0, 0), (0, 1), secret_hash([7; 32])));
+		System::assert_has_event(mock::Event::KittyModule(Event::BreedCommit(2, 0, 4)));
+		assert_eq!(KittyModule::pending_breeds(4), vec![0]);
+
+		//揭示区块之前随机数还没有确定
+		run_to_block(3);
+		assert_noop!(KittyModule::reveal_breed(Origin::signed(2), 0, [7; 32]), Error::<Test>::BreedNotRevealable);
+		run_to_block(4);
+		assert!(KittyModule::pending_breeds(4).is_empty());
+		assert_eq!(KittyModule::pending_breeds(9), vec![0]);
+
+		assert_noop!(KittyModule::reveal_breed(Origin::signed(1), 0, [7; 32]), Error::<Test>::NotOwner);
+		assert_noop!(KittyModule::reveal_breed(Origin::signed(2), 0, [8; 32]), Error::<Test>::SecretMismatch);
+		assert_ok!(KittyModule::reveal_breed(Origin::signed(2), 0, [7; 32]));
+		System::assert_has_event(mock::Event::KittyModule(Event::KittyBred(2, 0, (0, 2))));
+		assert_eq!(KittyModule::owner((0, 2)), Some(2));
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(KittyModule::breed_commits(0), None);
+
+		//子代的每个基因都来自父母之一
+		let (dna_1, dna_2) = (KittyModule::kitties((0, 0)).unwrap().dna.0, KittyModule::kitties((0, 1)).unwrap().dna.0);
+		let child = KittyModule::kitties((0, 2)).unwrap().dna.0;
+		for i in 0..16 {
+			assert_eq!(child[i] & !(dna_1[i] | dna_2[i]), 0);
+		}
+
+		//已揭示的提交不会再过期
+		run_to_block(9);
+		assert!(System::events().iter().all(|record| !matches!(
+			record.event,
+			mock::Event::KittyModule(Event::BreedExpired(..))
+		)));
+	})
+}
+
+#[test]
+fn breed_expires_without_reveal() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		let free = Balances::free_balance(1);
+		assert_ok!(KittyModule::breed(Origin::signed(1), (0, 0), (0, 1), secret_hash([7; 32])));
+
+		//揭示窗口结束后押金被罚没
+		run_to_block(9);
+		System::assert_has_event(mock::Event::KittyModule(Event::BreedExpired(1, 0)));
+		assert_eq!(Balances::reserved_balance(1), 2_000);
+		assert_eq!(Balances::free_balance(1), free - 100);
+		assert_noop!(KittyModule::reveal_breed(Origin::signed(1), 0, [7; 32]), Error::<Test>::InvalidBreedCommit);
+	})
+}
+
+#[test]
+fn breed_reveal_fails_without_parent() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		assert_ok!(KittyModule::breed(Origin::signed(1), (0, 0), (0, 1), secret_hash([7; 32])));
+		run_to_block(4);
+		assert_ok!(KittyModule::burn(Origin::signed(1), (0, 0)));
+
+		//父母之一已被销毁，退还押金但不产生子代
+		assert_ok!(KittyModule::reveal_breed(Origin::signed(1), 0, [7; 32]));
+		System::assert_has_event(mock::Event::KittyModule(Event::BreedRevealFailed(1, 0)));
+		assert_eq!(KittyModule::kitties((0, 2)), None);
+		assert_eq!(Balances::reserved_balance(1), 1_000);
 	})
 }
 
@@ -58,11 +217,20 @@ fn breed_work() {
 fn breed_failed() {
 	new_test_ext().execute_with(|| {
 		assert_ok!(KittyModule::create(Origin::signed(1)));
-		assert_noop!(KittyModule::breed(Origin::signed(1), 1, 1), Error::<Test>::SameParentIndex);
-		assert_noop!(KittyModule::breed(Origin::signed(1), 1, 2), Error::<Test>::InvalidKittyIndex);
+		let hash = secret_hash([7; 32]);
+		assert_noop!(KittyModule::breed(Origin::signed(1), (0, 0), (0, 0), hash), Error::<Test>::SameParentIndex);
+		assert_noop!(KittyModule::breed(Origin::signed(1), (0, 0), (0, 1), hash), Error::<Test>::InvalidKittyIndex);
 		assert_ok!(KittyModule::create(Origin::signed(1)));
-		KittiesCount::<Test>::put(u32::max_value());
-		assert_noop!(KittyModule::breed(Origin::signed(1), 1, 2), Error::<Test>::KittiesCountOverflow);
+		//账户5付不起押金
+		assert_noop!(KittyModule::breed(Origin::signed(5), (0, 0), (0, 1), hash), Error::<Test>::BalanceLitter);
+
+		//每个区块最多两次繁殖
+		assert_ok!(KittyModule::breed(Origin::signed(1), (0, 0), (0, 1), hash));
+		assert_ok!(KittyModule::breed(Origin::signed(1), (0, 0), (0, 1), hash));
+		assert_noop!(KittyModule::breed(Origin::signed(1), (0, 0), (0, 1), hash), Error::<Test>::TooManyBreeds);
+
+		KittiesCount::<Test>::insert(0, u32::max_value());
+		assert_noop!(KittyModule::breed(Origin::signed(1), (0, 0), (0, 1), hash), Error::<Test>::KittiesCountOverflow);
 	})
 }
 
@@ -70,10 +238,36 @@ fn breed_failed() {
 fn buy_kitty_work() {
 	new_test_ext().execute_with(|| {
 		assert_ok!(KittyModule::create(Origin::signed(1)));
-		assert_ok!(KittyModule::sell_kitty(Origin::signed(1), 1, Some(100)));
+		assert_ok!(KittyModule::sell_kitty(Origin::signed(1), (0, 0), Some(100)));
 
-		assert_ok!(KittyModule::buy_kitty(Origin::signed(2), 1));
-		assert_eq!(KittiesPrice::<Test>::contains_key(1), false);
+		assert_ok!(KittyModule::buy_kitty(Origin::signed(2), (0, 0)));
+		//出售只发出一个KittySold事件
+		System::assert_last_event(mock::Event::KittyModule(Event::KittySold(1, 2, (0, 0), SalePrice::Native(100))));
+		assert_eq!(KittyModule::kitties_price((0, 0)), None);
+		//押金随kitty转到买家
+		assert_eq!(KittyModule::kitties((0, 0)).unwrap().depositor, 2);
+		assert_eq!((Balances::reserved_balance(1), Balances::reserved_balance(2)), (0, 1_000));
+	})
+}
+
+#[test]
+fn buy_kitty_only_releases_recorded_deposit() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		assert_ok!(KittyModule::claim_starter_kitty(Origin::none(), 5, starter_signature(5)));
+		assert_ok!(KittyModule::transfer(Origin::signed(1), 5, (0, 0)));
+
+		//新手kitty没有押金，卖出时不能释放卖家其他kitty的押金
+		assert_ok!(KittyModule::sell_kitty(Origin::signed(5), (0, 1), Some(100)));
+		assert_ok!(KittyModule::buy_kitty(Origin::signed(2), (0, 1)));
+		assert_eq!(Balances::reserved_balance(1), 1_000);
+		assert_eq!(Balances::reserved_balance(2), 1_000);
+
+		//转出的kitty押金仍由创建者承担，卖出时才释放
+		assert_ok!(KittyModule::sell_kitty(Origin::signed(5), (0, 0), Some(100)));
+		assert_ok!(KittyModule::buy_kitty(Origin::signed(2), (0, 0)));
+		assert_eq!(Balances::reserved_balance(1), 0);
+		assert_eq!(Balances::reserved_balance(2), 2_000);
 	})
 }
 
@@ -81,15 +275,15 @@ fn buy_kitty_work() {
 fn buy_kitty_failed() {
 	new_test_ext().execute_with(|| {
 		//测试kitty id 无效
-		assert_noop!(KittyModule::buy_kitty(Origin::signed(1), 1), Error::<Test>::InvalidKittyIndex);
+		assert_noop!(KittyModule::buy_kitty(Origin::signed(1), (0, 0)), Error::<Test>::InvalidKittyIndex);
 		//测试owner
 		assert_ok!(KittyModule::create(Origin::signed(1)));
-		assert_noop!(KittyModule::buy_kitty(Origin::signed(1), 1), Error::<Test>::FromSameTo);
+		assert_noop!(KittyModule::buy_kitty(Origin::signed(1), (0, 0)), Error::<Test>::FromSameTo);
 		//测试没有可购买时
-		assert_noop!(KittyModule::buy_kitty(Origin::signed(2), 1), Error::<Test>::NotKittySale);
+		assert_noop!(KittyModule::buy_kitty(Origin::signed(2), (0, 0)), Error::<Test>::NotKittySale);
 		//测试没有balance
-		assert_ok!(KittyModule::sell_kitty(Origin::signed(1), 1, Some(100)));
-		assert_noop!(KittyModule::buy_kitty(Origin::signed(3), 1), Error::<Test>::BalanceLitter);
+		assert_ok!(KittyModule::sell_kitty(Origin::signed(1), (0, 0), Some(100)));
+		assert_noop!(KittyModule::buy_kitty(Origin::signed(3), (0, 0)), Error::<Test>::BalanceLitter);
 	})
 }
 
@@ -97,13 +291,501 @@ fn buy_kitty_failed() {
 fn sell_kitty_work() {
 	new_test_ext().execute_with(|| {
 		assert_ok!(KittyModule::create(Origin::signed(1)));
-		assert_ok!(KittyModule::sell_kitty(Origin::signed(1), 1, Some(100)));
+		assert_ok!(KittyModule::sell_kitty(Origin::signed(1), (0, 0), Some(100)));
 	})
 }
 
 #[test]
 fn sell_kitty_failed() {
 	new_test_ext().execute_with(|| {
-		assert_noop!(KittyModule::sell_kitty(Origin::signed(1), 1, Some(100)), Error::<Test>::FromSameTo);
+		assert_noop!(KittyModule::sell_kitty(Origin::signed(1), (0, 0), Some(100)), Error::<Test>::FromSameTo);
 	})
-}
\ No newline at end of file
+}
+#[test]
+fn create_collection_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittyModule::create_collection(Origin::signed(1), 2, Permill::from_percent(10), b"series".to_vec()));
+		System::assert_has_event(mock::Event::KittyModule(Event::CollectionCreate(1, 1)));
+		assert_eq!(KittyModule::collections(1).unwrap().owner, 1);
+		assert_eq!(Balances::reserved_balance(1), 10_000);
+	})
+}
+
+#[test]
+fn create_collection_failed() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			KittyModule::create_collection(Origin::signed(1), 2, Permill::zero(), vec![0; 33]),
+			Error::<Test>::MetadataTooLong
+		);
+		assert_noop!(
+			KittyModule::create_collection(Origin::signed(3), 2, Permill::zero(), vec![]),
+			Error::<Test>::BalanceLitter
+		);
+	})
+}
+
+#[test]
+fn mint_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittyModule::create_collection(Origin::signed(1), 2, Permill::zero(), vec![]));
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		assert_ok!(KittyModule::mint(Origin::signed(1), 1));
+		assert_ok!(KittyModule::mint(Origin::signed(1), 1));
+		System::assert_has_event(mock::Event::KittyModule(Event::CollectionMint(1, 1, 1)));
+		assert_eq!(KittyModule::owner((1, 1)), Some(1));
+		assert_eq!(KittyModule::kitties_count(0), 1);
+		assert_eq!(KittyModule::kitties_count(1), 2);
+		assert_noop!(KittyModule::mint(Origin::signed(1), 1), Error::<Test>::CollectionSoldOut);
+	})
+}
+
+#[test]
+fn mint_failed() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(KittyModule::mint(Origin::signed(1), 1), Error::<Test>::InvalidCollectionId);
+		assert_ok!(KittyModule::create_collection(Origin::signed(1), 2, Permill::zero(), vec![]));
+		assert_noop!(KittyModule::mint(Origin::signed(2), 1), Error::<Test>::NotCollectionOwner);
+		//公共系列不能由用户铸造
+		assert_noop!(KittyModule::mint(Origin::signed(1), 0), Error::<Test>::InvalidCollectionId);
+	})
+}
+
+#[test]
+fn set_collection_metadata_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittyModule::create_collection(Origin::signed(1), 2, Permill::zero(), vec![]));
+		assert_noop!(
+			KittyModule::set_collection_metadata(Origin::signed(2), 1, b"new".to_vec()),
+			Error::<Test>::NotCollectionOwner
+		);
+		assert_ok!(KittyModule::set_collection_metadata(Origin::signed(1), 1, b"new".to_vec()));
+		assert_eq!(KittyModule::collections(1).unwrap().metadata, b"new".to_vec());
+	})
+}
+
+#[test]
+fn destroy_collection_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittyModule::create_collection(Origin::signed(0), 2, Permill::from_percent(10), vec![]));
+		assert_ok!(KittyModule::mint(Origin::signed(0), 1));
+		assert_ok!(KittyModule::transfer(Origin::signed(0), 1, (1, 0)));
+		assert_noop!(KittyModule::destroy_collection(Origin::signed(1), 1), Error::<Test>::NotCollectionOwner);
+
+		let reserved = Balances::reserved_balance(0);
+		assert_ok!(KittyModule::destroy_collection(Origin::signed(0), 1));
+		System::assert_has_event(mock::Event::KittyModule(Event::CollectionDestroy(1)));
+		assert_eq!(Balances::reserved_balance(0), reserved - 10_000);
+		assert!(KittyModule::collections(1).is_none());
+		assert_noop!(KittyModule::mint(Origin::signed(0), 1), Error::<Test>::InvalidCollectionId);
+
+		//已铸造的kitty仍可交易，但不再支付版税
+		assert_ok!(KittyModule::sell_kitty(Origin::signed(1), (1, 0), Some(1_000)));
+		let creator_free = Balances::free_balance(0);
+		assert_ok!(KittyModule::buy_kitty(Origin::signed(2), (1, 0)));
+		assert_eq!(Balances::free_balance(0), creator_free);
+
+		//系列编号不会被重复使用
+		assert_ok!(KittyModule::create_collection(Origin::signed(0), 2, Permill::zero(), vec![]));
+		assert!(KittyModule::collections(2).is_some());
+	})
+}
+
+#[test]
+fn buy_kitty_pays_royalty() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittyModule::create_collection(Origin::signed(0), 2, Permill::from_percent(10), vec![]));
+		assert_ok!(KittyModule::mint(Origin::signed(0), 1));
+		assert_ok!(KittyModule::transfer(Origin::signed(0), 1, (1, 0)));
+		assert_ok!(KittyModule::sell_kitty(Origin::signed(1), (1, 0), Some(1_000)));
+
+		let creator_free = Balances::free_balance(0);
+		assert_ok!(KittyModule::buy_kitty(Origin::signed(2), (1, 0)));
+		assert_eq!(Balances::free_balance(0), creator_free + 100);
+	})
+}
+
+fn sell_order(seller: u64, kitty_id: (u32, u32), price: u128, nonce: u64) -> (SellOrderOf<Test>, TestSignature) {
+	let order = SellOrder { seller, kitty_id, price, expiry: 10, nonce };
+	let signature = TestSignature(seller, KittyModule::sell_order_message(&order));
+	(order, signature)
+}
+
+#[test]
+fn fill_order_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		let (order, signature) = sell_order(1, (0, 0), 100, 7);
+
+		assert_ok!(KittyModule::fill_order(Origin::signed(2), order, signature));
+		System::assert_has_event(mock::Event::KittyModule(Event::OrderFill(1, 2, (0, 0), 100)));
+		System::assert_has_event(mock::Event::KittyModule(Event::KittySold(1, 2, (0, 0), SalePrice::Native(100))));
+		assert_eq!(KittyModule::owner((0, 0)), Some(2));
+		assert!(KittyModule::used_nonces(1, 7));
+	})
+}
+
+#[test]
+fn fill_order_failed() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		//签名不匹配
+		let (order, _) = sell_order(1, (0, 0), 100, 7);
+		let forged = TestSignature(2, KittyModule::sell_order_message(&order));
+		assert_noop!(KittyModule::fill_order(Origin::signed(2), order.clone(), forged), Error::<Test>::InvalidSignature);
+		//签名缺少域前缀和创世哈希
+		let bare = TestSignature(1, order.encode());
+		assert_noop!(KittyModule::fill_order(Origin::signed(2), order, bare), Error::<Test>::InvalidSignature);
+		//卖家不是主人
+		let (order, signature) = sell_order(2, (0, 0), 100, 7);
+		assert_noop!(KittyModule::fill_order(Origin::signed(1), order, signature), Error::<Test>::NotOwner);
+		//订单已过期
+		System::set_block_number(11);
+		let (order, signature) = sell_order(1, (0, 0), 100, 7);
+		assert_noop!(KittyModule::fill_order(Origin::signed(2), order, signature), Error::<Test>::OrderExpired);
+	})
+}
+
+#[test]
+fn fill_order_cannot_replay() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		let (order, signature) = sell_order(1, (0, 0), 100, 7);
+		assert_ok!(KittyModule::fill_order(Origin::signed(2), order.clone(), signature.clone()));
+		assert_ok!(KittyModule::transfer(Origin::signed(2), 1, (0, 0)));
+		assert_noop!(KittyModule::fill_order(Origin::signed(2), order, signature), Error::<Test>::OrderNonceUsed);
+	})
+}
+
+#[test]
+fn cancel_order_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		let (order, signature) = sell_order(1, (0, 0), 100, 7);
+		assert_ok!(KittyModule::cancel_order(Origin::signed(1), 7));
+		assert_noop!(KittyModule::cancel_order(Origin::signed(1), 7), Error::<Test>::OrderNonceUsed);
+		assert_noop!(KittyModule::fill_order(Origin::signed(2), order, signature), Error::<Test>::OrderNonceUsed);
+	})
+}
+
+#[test]
+fn buy_kitty_with_asset_works() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Assets::force_create(Origin::root(), 0, 0, true, 1));
+		assert_ok!(Assets::mint(Origin::signed(0), 0, 2, 1_000));
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		assert_ok!(KittyModule::sell_kitty_for_asset(Origin::signed(1), (0, 0), 0, 300));
+		System::assert_has_event(mock::Event::KittyModule(Event::KittyAssetSale(1, (0, 0), 0, 300)));
+
+		assert_ok!(KittyModule::buy_kitty(Origin::signed(2), (0, 0)));
+		System::assert_has_event(mock::Event::KittyModule(Event::KittySold(1, 2, (0, 0), SalePrice::Asset(0, 300))));
+		assert_eq!(Assets::balance(0, 1), 300);
+		assert_eq!(Assets::balance(0, 2), 700);
+		assert_eq!(KittyModule::owner((0, 0)), Some(2));
+		assert!(KittyModule::kitties_asset_price((0, 0)).is_none());
+	})
+}
+
+#[test]
+fn buy_kitty_with_asset_failed() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Assets::force_create(Origin::root(), 0, 0, true, 1));
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		assert_noop!(
+			KittyModule::sell_kitty_for_asset(Origin::signed(2), (0, 0), 0, 300),
+			Error::<Test>::FromSameTo
+		);
+		assert_ok!(KittyModule::sell_kitty_for_asset(Origin::signed(1), (0, 0), 0, 300));
+		//没有足够的资产余额
+		assert!(KittyModule::buy_kitty(Origin::signed(2), (0, 0)).is_err());
+		assert_eq!(KittyModule::owner((0, 0)), Some(1));
+	})
+}
+
+#[test]
+fn sell_kitty_replaces_asset_listing() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		assert_ok!(KittyModule::sell_kitty_for_asset(Origin::signed(1), (0, 0), 0, 300));
+		assert_ok!(KittyModule::sell_kitty(Origin::signed(1), (0, 0), Some(100)));
+		assert!(KittyModule::kitties_asset_price((0, 0)).is_none());
+	})
+}
+
+#[test]
+fn hooks_are_called() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		assert_ok!(KittyModule::breed(Origin::signed(1), (0, 0), (0, 1), secret_hash([7; 32])));
+		assert_ok!(KittyModule::transfer(Origin::signed(1), 2, (0, 0)));
+		assert_ok!(KittyModule::sell_kitty(Origin::signed(1), (0, 1), Some(100)));
+		assert_ok!(KittyModule::buy_kitty(Origin::signed(2), (0, 1)));
+		run_to_block(4);
+		assert_ok!(KittyModule::reveal_breed(Origin::signed(1), 0, [7; 32]));
+		assert_ok!(KittyModule::burn(Origin::signed(2), (0, 0)));
+
+		CREATED.with(|c| assert_eq!(*c.borrow(), vec![(1, (0, 0)), (1, (0, 1)), (1, (0, 2))]));
+		TRANSFERRED.with(|t| assert_eq!(*t.borrow(), vec![(1, 2, (0, 0)), (1, 2, (0, 1))]));
+		BURNED.with(|b| assert_eq!(*b.borrow(), vec![(2, (0, 0))]));
+	})
+}
+
+#[test]
+fn history_records_transfers_and_sales() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		assert_ok!(KittyModule::transfer(Origin::signed(1), 2, (0, 0)));
+		System::set_block_number(5);
+		assert_ok!(KittyModule::sell_kitty(Origin::signed(2), (0, 0), Some(100)));
+		assert_ok!(KittyModule::buy_kitty(Origin::signed(1), (0, 0)));
+
+		assert_eq!(KittyModule::history_of((0, 0)), vec![
+			HistoryEntry { block_number: 1, from: 1, to: 2, price: None },
+			HistoryEntry { block_number: 5, from: 2, to: 1, price: Some(SalePrice::Native(100)) },
+		]);
+	})
+}
+
+#[test]
+fn history_is_pruned() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		assert_ok!(KittyModule::transfer(Origin::signed(1), 2, (0, 0)));
+		assert_ok!(KittyModule::transfer(Origin::signed(2), 0, (0, 0)));
+		assert_ok!(KittyModule::transfer(Origin::signed(0), 1, (0, 0)));
+
+		let history = KittyModule::history_of((0, 0));
+		assert_eq!(history.len(), 2);
+		assert_eq!((history[0].from, history[1].to), (2, 1));
+	})
+}
+
+#[test]
+fn locked_kitty_cannot_move() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		assert_ok!(KittyModule::sell_kitty(Origin::signed(1), (0, 0), Some(100)));
+		assert_ok!(KittyModule::lock((0, 0)));
+		assert_noop!(KittyModule::lock((0, 0)), Error::<Test>::KittyLocked);
+
+		assert_noop!(KittyModule::transfer(Origin::signed(1), 2, (0, 0)), Error::<Test>::KittyLocked);
+		assert_noop!(KittyModule::buy_kitty(Origin::signed(2), (0, 0)), Error::<Test>::KittyLocked);
+		assert_noop!(KittyModule::sell_kitty(Origin::signed(1), (0, 0), Some(50)), Error::<Test>::KittyLocked);
+		assert_noop!(KittyModule::sell_kitty_for_asset(Origin::signed(1), (0, 0), 0, 50), Error::<Test>::KittyLocked);
+
+		KittyModule::unlock((0, 0));
+		assert_ok!(KittyModule::transfer(Origin::signed(1), 2, (0, 0)));
+	})
+}
+
+#[test]
+fn query_helpers_work() {
+	new_test_ext().execute_with(|| {
+		for _ in 0..3 {
+			assert_ok!(KittyModule::create(Origin::signed(1)));
+		}
+		assert_ok!(KittyModule::create(Origin::signed(2)));
+		assert_ok!(KittyModule::sell_kitty(Origin::signed(1), (0, 0), Some(100)));
+		assert_ok!(KittyModule::sell_kitty(Origin::signed(1), (0, 2), Some(300)));
+		assert_ok!(KittyModule::sell_kitty(Origin::signed(2), (0, 3), Some(400)));
+
+		let ids = |kitties: Vec<((u32, u32), KittyInfoOf<Test>)>| kitties.into_iter().map(|(id, _)| id).collect::<Vec<_>>();
+		assert_eq!(ids(KittyModule::kitties_of(&1, None, 10)), vec![(0, 0), (0, 1), (0, 2)]);
+		assert_eq!(ids(KittyModule::kitties_of(&1, Some((0, 0)), 1)), vec![(0, 1)]);
+		assert_eq!(ids(KittyModule::kitties_of(&3, None, 10)), vec![]);
+		assert_eq!(ids(KittyModule::listings(None, 2)), vec![(0, 0), (0, 2)]);
+		assert_eq!(ids(KittyModule::listings(Some((0, 2)), 2)), vec![(0, 3)]);
+		assert_eq!(KittyModule::listings(Some((0, 2)), 2)[0].1.price, Some(400));
+
+		//转移和成交后索引跟着更新
+		assert_ok!(KittyModule::buy_kitty(Origin::signed(2), (0, 0)));
+		assert_ok!(KittyModule::transfer(Origin::signed(1), 3, (0, 1)));
+		assert_ok!(KittyModule::sell_kitty(Origin::signed(1), (0, 2), None));
+		assert_eq!(ids(KittyModule::kitties_of(&1, None, 10)), vec![(0, 2)]);
+		assert_eq!(ids(KittyModule::kitties_of(&2, None, 10)), vec![(0, 0), (0, 3)]);
+		assert_eq!(ids(KittyModule::kitties_of(&3, None, 10)), vec![(0, 1)]);
+		assert_eq!(ids(KittyModule::listings(None, 10)), vec![(0, 3)]);
+
+		let dna = KittyModule::kitties((0, 0)).unwrap().dna;
+		assert_eq!(KittyModule::phenotype((0, 0)), Some(dna.phenotype()));
+		assert_eq!(KittyModule::phenotype((0, 9)), None);
+	})
+}
+
+fn starter_signature(who: u64) -> TestSignature {
+	TestSignature(who, KittyModule::starter_claim_message(&who))
+}
+
+#[test]
+fn claim_starter_kitty_works() {
+	new_test_ext().execute_with(|| {
+		//账户5没有任何余额
+		assert_ok!(KittyModule::claim_starter_kitty(Origin::none(), 5, starter_signature(5)));
+		System::assert_has_event(mock::Event::KittyModule(Event::KittyCreate(5, (0, 0))));
+		assert_eq!(KittyModule::owner((0, 0)), Some(5));
+		assert_eq!(Balances::reserved_balance(5), 0);
+		assert!(KittyModule::starter_claimed(5));
+
+		assert_noop!(
+			KittyModule::claim_starter_kitty(Origin::none(), 5, starter_signature(5)),
+			Error::<Test>::StarterAlreadyClaimed
+		);
+	})
+}
+
+#[test]
+fn claim_starter_kitty_failed() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			KittyModule::claim_starter_kitty(Origin::signed(5), 5, starter_signature(5)),
+			sp_runtime::traits::BadOrigin
+		);
+		assert_noop!(
+			KittyModule::claim_starter_kitty(Origin::none(), 5, starter_signature(6)),
+			Error::<Test>::InvalidSignature
+		);
+
+		//每个区块最多领取两只
+		assert_ok!(KittyModule::claim_starter_kitty(Origin::none(), 5, starter_signature(5)));
+		assert_ok!(KittyModule::claim_starter_kitty(Origin::none(), 6, starter_signature(6)));
+		assert_noop!(
+			KittyModule::claim_starter_kitty(Origin::none(), 7, starter_signature(7)),
+			Error::<Test>::TooManyStarterClaims
+		);
+		run_to_block(2);
+		assert_ok!(KittyModule::claim_starter_kitty(Origin::none(), 7, starter_signature(7)));
+
+		//全链最多领取三只
+		assert_noop!(
+			KittyModule::claim_starter_kitty(Origin::none(), 8, starter_signature(8)),
+			Error::<Test>::StarterKittiesExhausted
+		);
+	})
+}
+
+#[test]
+fn claim_starter_kitty_requires_new_account() {
+	new_test_ext().execute_with(|| {
+		//发过交易的账户
+		System::inc_account_nonce(&5);
+		assert_noop!(
+			KittyModule::claim_starter_kitty(Origin::none(), 5, starter_signature(5)),
+			Error::<Test>::NotNewAccount
+		);
+
+		//已经拥有kitty的账户
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		assert_ok!(KittyModule::transfer(Origin::signed(1), 6, (0, 0)));
+		assert_noop!(
+			KittyModule::claim_starter_kitty(Origin::none(), 6, starter_signature(6)),
+			Error::<Test>::NotNewAccount
+		);
+	})
+}
+
+#[test]
+fn validate_starter_claim() {
+	use sp_runtime::{traits::ValidateUnsigned, transaction_validity::{InvalidTransaction, TransactionSource}};
+
+	new_test_ext().execute_with(|| {
+		let validate = |who, signature| KittyModule::validate_unsigned(
+			TransactionSource::External,
+			&crate::Call::<Test>::claim_starter_kitty(who, signature),
+		);
+		assert!(validate(5, starter_signature(5)).is_ok());
+		assert_eq!(validate(5, starter_signature(6)), Err(InvalidTransaction::BadProof.into()));
+
+		assert_ok!(KittyModule::claim_starter_kitty(Origin::none(), 5, starter_signature(5)));
+		assert_eq!(validate(5, starter_signature(5)), Err(InvalidTransaction::Stale.into()));
+		System::inc_account_nonce(&6);
+		assert_eq!(validate(6, starter_signature(6)), Err(InvalidTransaction::Stale.into()));
+		assert_eq!(
+			KittyModule::validate_unsigned(TransactionSource::External, &crate::Call::<Test>::cancel_order(0)),
+			Err(InvalidTransaction::Call.into())
+		);
+	})
+}
+
+fn pallet_prefix() -> &'static [u8] {
+	<KittyModule as PalletInfoAccess>::name().as_bytes()
+}
+
+//按V1存储格式写入一只kitty
+fn put_v1_kitty(kitty_id: u32, dna: [u8; 16], owner: Option<u64>, price: Option<u128>) {
+	let key = kitty_id.using_encoded(Blake2_128Concat::hash);
+	put_storage_value(pallet_prefix(), b"Kitties", &key, Some(Kitty(dna)));
+	if let Some(owner) = owner {
+		put_storage_value(pallet_prefix(), b"Owner", &key, Some(owner));
+	}
+	put_storage_value(pallet_prefix(), b"KittiesPrice", &key, price);
+}
+
+fn kitty_info(owner: u64, price: Option<u128>, deposit: u128) -> KittyInfoOf<Test> {
+	KittyInfo { dna: Kitty([owner as u8; 16]), owner, price, depositor: owner, deposit }
+}
+
+//V1的全局kitty计数
+fn put_global_kitties_count(count: u32) {
+	put_storage_value(pallet_prefix(), b"KittiesCount", &[], count);
+}
+
+#[test]
+fn migrate_to_kitty_info_works() {
+	new_test_ext().execute_with(|| {
+		put_v1_kitty(0, [1u8; 16], Some(1), Some(500));
+		put_v1_kitty(1, [2u8; 16], Some(2), None);
+		put_v1_kitty(2, [1u8; 16], Some(1), None);
+		put_global_kitties_count(3);
+		//账户1只为一只半kitty预留了押金，账户2的kitty是免押金领取的
+		assert_ok!(Balances::reserve(&1, 1_500));
+		assert_eq!(KittyModule::storage_version(), Releases::V1);
+
+		assert_ok!(migrations::v2::pre_migrate::<Test>());
+		migrations::v2::migrate::<Test>();
+		assert_ok!(migrations::v2::post_migrate::<Test>());
+		assert_eq!(KittyModule::storage_version(), Releases::V2);
+
+		assert_eq!(KittyModule::kitties((0, 0)), Some(kitty_info(1, Some(500), 1_000)));
+		assert_eq!(KittyModule::kitties((0, 1)), Some(kitty_info(2, None, 0)));
+		assert_eq!(KittyModule::kitties((0, 2)), Some(kitty_info(1, None, 500)));
+		let ids = |kitties: Vec<((u32, u32), KittyInfoOf<Test>)>| kitties.into_iter().map(|(id, _)| id).collect::<Vec<_>>();
+		assert_eq!(ids(KittyModule::kitties_of(&1, None, 10)), vec![(0, 0), (0, 2)]);
+		assert_eq!(ids(KittyModule::listings(None, 10)), vec![(0, 0)]);
+
+		//迁移后的kitty可以正常交易，押金随kitty转到买家
+		assert_ok!(KittyModule::buy_kitty(Origin::signed(2), (0, 0)));
+		assert_eq!(KittyModule::owner((0, 0)), Some(2));
+		assert_eq!(Balances::reserved_balance(1), 500);
+
+		//新的编号从迁移前的计数之后开始
+		assert_ok!(KittyModule::create(Origin::signed(1)));
+		assert_eq!(KittyModule::owner((0, 3)), Some(1));
+	})
+}
+
+#[test]
+fn migrate_runs_once() {
+	new_test_ext().execute_with(|| {
+		put_v1_kitty(0, [1u8; 16], Some(1), None);
+		put_global_kitties_count(1);
+		KittyModule::on_runtime_upgrade();
+		assert_ok!(KittyModule::sell_kitty(Origin::signed(1), (0, 0), Some(100)));
+
+		KittyModule::on_runtime_upgrade();
+		assert_eq!(KittyModule::kitties_price((0, 0)), Some(100));
+	})
+}
+
+#[test]
+fn migrate_drops_kitty_without_owner() {
+	new_test_ext().execute_with(|| {
+		put_v1_kitty(0, [1u8; 16], None, None);
+		put_global_kitties_count(1);
+		assert_eq!(migrations::v2::pre_migrate::<Test>(), Err("kitty without an owner"));
+
+		migrations::v2::migrate::<Test>();
+		assert_ok!(migrations::v2::post_migrate::<Test>());
+		assert_eq!(KittyModule::kitties((0, 0)), None);
+	})
+}
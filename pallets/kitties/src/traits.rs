@@ -0,0 +1,19 @@
+//! Hooks other pallets can implement to react to kitty movements.
+
+/// Called after a kitty has been created, by `create`, `breed` or `mint`.
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+pub trait OnKittyCreated<AccountId, KittyId> {
+    fn on_kitty_created(owner: &AccountId, kitty_id: &KittyId);
+}
+
+/// Called after a kitty has changed hands, by `transfer` or a sale.
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+pub trait OnKittyTransferred<AccountId, KittyId> {
+    fn on_kitty_transferred(from: &AccountId, to: &AccountId, kitty_id: &KittyId);
+}
+
+/// Called after a kitty has been removed from the pallet for good.
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+pub trait OnKittyBurned<AccountId, KittyId> {
+    fn on_kitty_burned(owner: &AccountId, kitty_id: &KittyId);
+}
@@ -0,0 +1,207 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+    use frame_support::{pallet_prelude::*, transactional, traits::{Currency, ExistenceRequirement}};
+    use frame_system::pallet_prelude::*;
+    use sp_runtime::{RuntimeDebug, traits::Saturating};
+    use codec::{Encode, Decode};
+    use pallet_kitties::{BalanceOf, KittyIdOf};
+
+    /// A loan secured by a kitty. It is a request until a lender funds it.
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+    pub struct Loan<AccountId, Balance, BlockNumber> {
+        pub borrower: AccountId,
+        pub principal: Balance,
+        /// The amount owed on top of the principal.
+        pub interest: Balance,
+        /// The number of blocks the borrower has to repay once funded.
+        pub duration: BlockNumber,
+        /// `Some((lender, deadline))` once funded.
+        pub funded: Option<(AccountId, BlockNumber)>,
+    }
+
+    pub type LoanOf<T> = Loan<
+        <T as frame_system::Config>::AccountId,
+        BalanceOf<T>,
+        <T as frame_system::Config>::BlockNumber,
+    >;
+
+    #[pallet::config]
+    pub trait Config: pallet_kitties::Config {
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+        /// The longest a borrower can ask for to repay a loan.
+        type MaxLoanDuration: Get<Self::BlockNumber>;
+    }
+
+    #[pallet::event]
+    #[pallet::metadata(T::AccountId = "AccountId")]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// [borrower, kitty_id, principal, interest, duration]
+        LoanRequest(T::AccountId, KittyIdOf<T>, BalanceOf<T>, BalanceOf<T>, T::BlockNumber),
+        LoanCancel(KittyIdOf<T>),
+        /// [lender, kitty_id, deadline]
+        LoanFund(T::AccountId, KittyIdOf<T>, T::BlockNumber),
+        LoanRepay(T::AccountId, KittyIdOf<T>),
+        /// The borrower defaulted and the lender took the kitty. [lender, kitty_id]
+        CollateralClaim(T::AccountId, KittyIdOf<T>),
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        NotOwner,
+        InvalidLoan,
+        NotBorrower,
+        NotLender,
+        AlreadyFunded,
+        NotFunded,
+        LoanNotDue,
+        FromSameTo,
+        InvalidDuration,
+        TermsChanged,
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    pub struct Pallet<T>(_);
+
+    /// Loans keyed by their collateral; a locked kitty backs at most one loan.
+    #[pallet::storage]
+    #[pallet::getter(fn loans)]
+    pub type Loans<T: Config> = StorageMap<_, Blake2_128Concat, KittyIdOf<T>, LoanOf<T>>;
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        //用kitty作抵押申请借款，kitty被锁定
+        #[pallet::weight(0)]
+        pub fn request_loan(
+            origin: OriginFor<T>,
+            kitty_id: KittyIdOf<T>,
+            principal: BalanceOf<T>,
+            interest: BalanceOf<T>,
+            duration: T::BlockNumber,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(Some(who.clone()) == pallet_kitties::Pallet::<T>::owner(kitty_id), Error::<T>::NotOwner);
+            ensure!(duration <= T::MaxLoanDuration::get(), Error::<T>::InvalidDuration);
+            pallet_kitties::Pallet::<T>::lock(kitty_id)?;
+
+            Loans::<T>::insert(kitty_id, Loan {
+                borrower: who.clone(),
+                principal,
+                interest,
+                duration,
+                funded: None,
+            });
+
+            Self::deposit_event(Event::LoanRequest(who, kitty_id, principal, interest, duration));
+
+            Ok(())
+        }
+
+        //在放款前撤回借款申请
+        #[pallet::weight(0)]
+        pub fn cancel_request(origin: OriginFor<T>, kitty_id: KittyIdOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let loan = Self::loans(kitty_id).ok_or(Error::<T>::InvalidLoan)?;
+            ensure!(loan.borrower == who, Error::<T>::NotBorrower);
+            ensure!(loan.funded.is_none(), Error::<T>::AlreadyFunded);
+
+            Loans::<T>::remove(kitty_id);
+            pallet_kitties::Pallet::<T>::unlock(kitty_id);
+
+            Self::deposit_event(Event::LoanCancel(kitty_id));
+
+            Ok(())
+        }
+
+        //出借人放款，须给出所同意的借款条件，借款人撤回后以新条件重新申请时放款失败
+        #[pallet::weight(0)]
+        pub fn fund_loan(
+            origin: OriginFor<T>,
+            kitty_id: KittyIdOf<T>,
+            expected_principal: BalanceOf<T>,
+            expected_interest: BalanceOf<T>,
+            expected_duration: T::BlockNumber,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let mut loan = Self::loans(kitty_id).ok_or(Error::<T>::InvalidLoan)?;
+            ensure!(loan.funded.is_none(), Error::<T>::AlreadyFunded);
+            ensure!(loan.borrower != who, Error::<T>::FromSameTo);
+            ensure!(
+                loan.principal == expected_principal
+                    && loan.interest == expected_interest
+                    && loan.duration == expected_duration,
+                Error::<T>::TermsChanged
+            );
+
+            <T as pallet_kitties::Config>::Currency::transfer(
+                &who,
+                &loan.borrower,
+                loan.principal,
+                ExistenceRequirement::KeepAlive,
+            )?;
+            let deadline = <frame_system::Pallet<T>>::block_number().saturating_add(loan.duration);
+            loan.funded = Some((who.clone(), deadline));
+            Loans::<T>::insert(kitty_id, loan);
+
+            Self::deposit_event(Event::LoanFund(who, kitty_id, deadline));
+
+            Ok(())
+        }
+
+        //借款人还本付息，解锁kitty；出借人取走抵押物之前都可以还款
+        #[pallet::weight(0)]
+        #[transactional]
+        pub fn repay(origin: OriginFor<T>, kitty_id: KittyIdOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let loan = Self::loans(kitty_id).ok_or(Error::<T>::InvalidLoan)?;
+            ensure!(loan.borrower == who, Error::<T>::NotBorrower);
+            let (lender, _) = loan.funded.ok_or(Error::<T>::NotFunded)?;
+
+            <T as pallet_kitties::Config>::Currency::transfer(
+                &who,
+                &lender,
+                loan.principal.saturating_add(loan.interest),
+                ExistenceRequirement::KeepAlive,
+            )?;
+            Loans::<T>::remove(kitty_id);
+            pallet_kitties::Pallet::<T>::unlock(kitty_id);
+
+            Self::deposit_event(Event::LoanRepay(who, kitty_id));
+
+            Ok(())
+        }
+
+        //借款逾期，出借人取走抵押的kitty
+        #[pallet::weight(0)]
+        #[transactional]
+        pub fn claim_collateral(origin: OriginFor<T>, kitty_id: KittyIdOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let loan = Self::loans(kitty_id).ok_or(Error::<T>::InvalidLoan)?;
+            let (lender, deadline) = loan.funded.ok_or(Error::<T>::NotFunded)?;
+            ensure!(lender == who, Error::<T>::NotLender);
+            ensure!(<frame_system::Pallet<T>>::block_number() > deadline, Error::<T>::LoanNotDue);
+
+            Loans::<T>::remove(kitty_id);
+            pallet_kitties::Pallet::<T>::unlock(kitty_id);
+            pallet_kitties::Pallet::<T>::do_transfer(&loan.borrower, &who, kitty_id)?;
+
+            Self::deposit_event(Event::CollateralClaim(who, kitty_id));
+
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use crate as pallet_loans;
+use frame_support::parameter_types;
+
+pallet_kitties::kitty_test_runtime! {
+	LoanModule: pallet_loans::{Pallet, Call, Storage, Event<T>},
+}
+
+parameter_types! {
+	pub const MaxLoanDuration: u64 = 100;
+}
+
+impl pallet_loans::Config for Test {
+	type Event = Event;
+	type MaxLoanDuration = MaxLoanDuration;
+}
@@ -0,0 +1,94 @@
+use crate::{Error, Event, mock::*};
+use frame_support::{assert_ok, assert_noop};
+use super::*;
+
+#[test]
+fn request_loan_locks_kitty() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1]);
+		assert_noop!(LoanModule::request_loan(Origin::signed(2), (0, 0), 1_000, 100, 10), Error::<Test>::NotOwner);
+		assert_noop!(LoanModule::request_loan(Origin::signed(1), (0, 0), 1_000, 100, 101), Error::<Test>::InvalidDuration);
+		assert_ok!(LoanModule::request_loan(Origin::signed(1), (0, 0), 1_000, 100, 10));
+		System::assert_has_event(mock::Event::LoanModule(Event::LoanRequest(1, (0, 0), 1_000, 100, 10)));
+
+		assert!(KittyModule::is_locked((0, 0)));
+		assert_noop!(
+			KittyModule::transfer(Origin::signed(1), 2, (0, 0)),
+			pallet_kitties::Error::<Test>::KittyLocked
+		);
+		assert_noop!(
+			LoanModule::request_loan(Origin::signed(1), (0, 0), 1_000, 100, 10),
+			pallet_kitties::Error::<Test>::KittyLocked
+		);
+	})
+}
+
+#[test]
+fn cancel_request_works() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1]);
+		assert_ok!(LoanModule::request_loan(Origin::signed(1), (0, 0), 1_000, 100, 10));
+		assert_noop!(LoanModule::cancel_request(Origin::signed(2), (0, 0)), Error::<Test>::NotBorrower);
+		assert_ok!(LoanModule::cancel_request(Origin::signed(1), (0, 0)));
+		assert!(!KittyModule::is_locked((0, 0)));
+		assert!(LoanModule::loans((0, 0)).is_none());
+	})
+}
+
+#[test]
+fn fund_and_repay_works() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1]);
+		assert_ok!(LoanModule::request_loan(Origin::signed(1), (0, 0), 1_000, 100, 10));
+		assert_noop!(LoanModule::fund_loan(Origin::signed(1), (0, 0), 1_000, 100, 10), Error::<Test>::FromSameTo);
+		assert_noop!(LoanModule::repay(Origin::signed(1), (0, 0)), Error::<Test>::NotFunded);
+
+		assert_ok!(LoanModule::fund_loan(Origin::signed(2), (0, 0), 1_000, 100, 10));
+		System::assert_has_event(mock::Event::LoanModule(Event::LoanFund(2, (0, 0), 11)));
+		assert_noop!(LoanModule::fund_loan(Origin::signed(0), (0, 0), 1_000, 100, 10), Error::<Test>::AlreadyFunded);
+		assert_noop!(LoanModule::cancel_request(Origin::signed(1), (0, 0)), Error::<Test>::AlreadyFunded);
+		assert_eq!(Balances::free_balance(2), 100_000_000 - 1_000);
+
+		assert_ok!(LoanModule::repay(Origin::signed(1), (0, 0)));
+		assert_eq!(Balances::free_balance(2), 100_000_000 + 100);
+		assert!(!KittyModule::is_locked((0, 0)));
+		assert_eq!(KittyModule::owner((0, 0)), Some(1));
+	})
+}
+
+#[test]
+fn claim_collateral_works() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1]);
+		assert_ok!(LoanModule::request_loan(Origin::signed(1), (0, 0), 1_000, 100, 10));
+		assert_noop!(LoanModule::claim_collateral(Origin::signed(2), (0, 0)), Error::<Test>::NotFunded);
+		assert_ok!(LoanModule::fund_loan(Origin::signed(2), (0, 0), 1_000, 100, 10));
+
+		assert_noop!(LoanModule::claim_collateral(Origin::signed(0), (0, 0)), Error::<Test>::NotLender);
+		System::set_block_number(11);
+		assert_noop!(LoanModule::claim_collateral(Origin::signed(2), (0, 0)), Error::<Test>::LoanNotDue);
+		System::set_block_number(12);
+		assert_ok!(LoanModule::claim_collateral(Origin::signed(2), (0, 0)));
+
+		assert_eq!(KittyModule::owner((0, 0)), Some(2));
+		assert!(!KittyModule::is_locked((0, 0)));
+		assert!(LoanModule::loans((0, 0)).is_none());
+	})
+}
+
+#[test]
+fn fund_loan_checks_terms() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1]);
+		assert_ok!(LoanModule::request_loan(Origin::signed(1), (0, 0), 1_000, 100, 10));
+
+		//借款人撤回后以更差的条件重新申请，按旧条件放款失败
+		assert_ok!(LoanModule::cancel_request(Origin::signed(1), (0, 0)));
+		assert_ok!(LoanModule::request_loan(Origin::signed(1), (0, 0), 2_000, 0, 100));
+		assert_noop!(
+			LoanModule::fund_loan(Origin::signed(2), (0, 0), 1_000, 100, 10),
+			Error::<Test>::TermsChanged
+		);
+		assert_ok!(LoanModule::fund_loan(Origin::signed(2), (0, 0), 2_000, 0, 100));
+	})
+}
@@ -0,0 +1,211 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub use pallet::*;
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+    use frame_support::{
+        pallet_prelude::*, transactional, BoundedVec, CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound,
+        traits::{Randomness, ReservableCurrency, Currency, BalanceStatus},
+    };
+    use frame_system::pallet_prelude::*;
+    use sp_runtime::traits::{Saturating, Zero};
+    use sp_std::prelude::*;
+    use codec::{Encode, Decode};
+    use pallet_kitties::{BalanceOf, KittyIdOf};
+
+    /// A kitty offered to whoever holds the winning ticket.
+    #[derive(Encode, Decode, CloneNoBound, PartialEqNoBound, EqNoBound, RuntimeDebugNoBound)]
+    pub struct Raffle<T: Config> {
+        pub owner: T::AccountId,
+        pub ticket_price: BalanceOf<T>,
+        pub max_tickets: u32,
+        /// The block at which the winner is drawn; tickets are sold until the block before.
+        pub end_block: T::BlockNumber,
+        /// One entry per ticket sold, in order of purchase.
+        pub tickets: BoundedVec<T::AccountId, T::MaxTickets>,
+    }
+
+    pub type RaffleOf<T> = Raffle<T>;
+
+    #[pallet::config]
+    pub trait Config: pallet_kitties::Config {
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+        /// The number of tickets that must be sold for a raffle to be drawn; otherwise all are refunded.
+        type MinTickets: Get<u32>;
+        /// The maximum number of tickets one raffle can sell.
+        type MaxTickets: Get<u32>;
+        /// The maximum number of raffles drawn in the same block.
+        type MaxRafflesPerBlock: Get<u32>;
+    }
+
+    #[pallet::event]
+    #[pallet::metadata(T::AccountId = "AccountId")]
+    #[pallet::generate_deposit(pub(super) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// [owner, kitty_id, ticket_price, max_tickets, end_block]
+        RaffleStart(T::AccountId, KittyIdOf<T>, BalanceOf<T>, u32, T::BlockNumber),
+        TicketBuy(T::AccountId, KittyIdOf<T>),
+        /// [winner, kitty_id, proceeds paid to the owner]
+        RaffleWin(T::AccountId, KittyIdOf<T>, BalanceOf<T>),
+        RaffleRefund(KittyIdOf<T>),
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        NotOwner,
+        RaffleExists,
+        InvalidRaffle,
+        InvalidEndBlock,
+        InvalidMaxTickets,
+        InvalidTicketPrice,
+        TooManyRaffles,
+        RaffleEnded,
+        SoldOut,
+        FromSameTo,
+        BalanceLitter,
+    }
+
+    #[pallet::pallet]
+    #[pallet::generate_store(pub(super) trait Store)]
+    pub struct Pallet<T>(_);
+
+    #[pallet::storage]
+    #[pallet::getter(fn raffles)]
+    pub type Raffles<T: Config> = StorageMap<_, Blake2_128Concat, KittyIdOf<T>, RaffleOf<T>>;
+
+    /// Raffles to draw at a given block.
+    #[pallet::storage]
+    #[pallet::getter(fn raffles_ending)]
+    pub type RafflesEnding<T: Config> = StorageMap<_, Twox64Concat, T::BlockNumber, Vec<KittyIdOf<T>>, ValueQuery>;
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_initialize(now: T::BlockNumber) -> Weight {
+            let ending = RafflesEnding::<T>::take(now);
+            let mut weight = T::DbWeight::get().reads_writes(1, 1);
+
+            for kitty_id in ending {
+                if let Some(raffle) = Raffles::<T>::take(kitty_id) {
+                    weight = weight.saturating_add(
+                        T::DbWeight::get().reads_writes(3, raffle.tickets.len() as u64 + 3)
+                    );
+                    Self::draw(kitty_id, raffle);
+                }
+            }
+
+            weight
+        }
+    }
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        //发起抽奖，kitty在开奖前被锁定，不能转让、出售或销毁
+        #[pallet::weight(0)]
+        #[transactional]
+        pub fn start_raffle(
+            origin: OriginFor<T>,
+            kitty_id: KittyIdOf<T>,
+            ticket_price: BalanceOf<T>,
+            max_tickets: u32,
+            end_block: T::BlockNumber,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(Some(who.clone()) == pallet_kitties::Pallet::<T>::owner(kitty_id), Error::<T>::NotOwner);
+            ensure!(!Raffles::<T>::contains_key(kitty_id), Error::<T>::RaffleExists);
+            ensure!(end_block > <frame_system::Pallet<T>>::block_number(), Error::<T>::InvalidEndBlock);
+            ensure!(max_tickets >= T::MinTickets::get().max(1), Error::<T>::InvalidMaxTickets);
+            ensure!(max_tickets <= T::MaxTickets::get(), Error::<T>::InvalidMaxTickets);
+            ensure!(!ticket_price.is_zero(), Error::<T>::InvalidTicketPrice);
+            let ending = RafflesEnding::<T>::decode_len(end_block).unwrap_or(0);
+            ensure!(ending < T::MaxRafflesPerBlock::get() as usize, Error::<T>::TooManyRaffles);
+
+            pallet_kitties::Pallet::<T>::lock(kitty_id)?;
+            Raffles::<T>::insert(kitty_id, Raffle {
+                owner: who.clone(),
+                ticket_price,
+                max_tickets,
+                end_block,
+                tickets: BoundedVec::default(),
+            });
+            RafflesEnding::<T>::append(end_block, kitty_id);
+
+            Self::deposit_event(Event::RaffleStart(who, kitty_id, ticket_price, max_tickets, end_block));
+
+            Ok(())
+        }
+
+        //购买抽奖券，票款在开奖前处于锁定状态
+        #[pallet::weight(0)]
+        pub fn buy_ticket(origin: OriginFor<T>, kitty_id: KittyIdOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Raffles::<T>::try_mutate(kitty_id, |maybe_raffle| -> DispatchResult {
+                let raffle = maybe_raffle.as_mut().ok_or(Error::<T>::InvalidRaffle)?;
+                ensure!(<frame_system::Pallet<T>>::block_number() < raffle.end_block, Error::<T>::RaffleEnded);
+                ensure!((raffle.tickets.len() as u32) < raffle.max_tickets, Error::<T>::SoldOut);
+                ensure!(who != raffle.owner, Error::<T>::FromSameTo);
+
+                raffle.tickets.try_push(who.clone()).map_err(|_| Error::<T>::SoldOut)?;
+                <T as pallet_kitties::Config>::Currency::reserve(&who, raffle.ticket_price)
+                    .map_err(|_| Error::<T>::BalanceLitter)?;
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::TicketBuy(who, kitty_id));
+
+            Ok(())
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Pick a winner and settle, or refund every ticket if the raffle cannot go ahead.
+        ///
+        /// The kitty has been locked since the raffle started, so it is released here and only
+        /// paid for once it has actually reached the winner.
+        fn draw(kitty_id: KittyIdOf<T>, raffle: RaffleOf<T>) {
+            pallet_kitties::Pallet::<T>::unlock(kitty_id);
+
+            let winner = if (raffle.tickets.len() as u32) < T::MinTickets::get().max(1) {
+                None
+            } else {
+                let subject = (b"kitty-raffle", kitty_id).encode();
+                let (seed, _) = <T as pallet_kitties::Config>::Randomness::random(&subject);
+                let roll = u32::decode(&mut seed.as_ref()).unwrap_or_default();
+                let winner = raffle.tickets[roll as usize % raffle.tickets.len()].clone();
+                pallet_kitties::Pallet::<T>::do_transfer(&raffle.owner, &winner, kitty_id).ok().map(|_| winner)
+            };
+
+            let winner = match winner {
+                Some(winner) => winner,
+                None => {
+                    for who in raffle.tickets.iter() {
+                        <T as pallet_kitties::Config>::Currency::unreserve(who, raffle.ticket_price);
+                    }
+                    Self::deposit_event(Event::RaffleRefund(kitty_id));
+                    return;
+                },
+            };
+
+            let mut proceeds = BalanceOf::<T>::default();
+            for who in raffle.tickets.iter() {
+                // The leftover is the part of the ticket price that could not be moved.
+                if let Ok(leftover) = <T as pallet_kitties::Config>::Currency::repatriate_reserved(
+                    who,
+                    &raffle.owner,
+                    raffle.ticket_price,
+                    BalanceStatus::Free,
+                ) {
+                    proceeds = proceeds.saturating_add(raffle.ticket_price.saturating_sub(leftover));
+                }
+            }
+
+            Self::deposit_event(Event::RaffleWin(winner, kitty_id, proceeds));
+        }
+    }
+}
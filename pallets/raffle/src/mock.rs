@@ -0,0 +1,26 @@
+use crate as pallet_raffle;
+use frame_support::{parameter_types, traits::Hooks};
+
+pallet_kitties::kitty_test_runtime! {
+	RaffleModule: pallet_raffle::{Pallet, Call, Storage, Event<T>},
+}
+
+parameter_types! {
+	pub const MinTickets: u32 = 2;
+	pub const MaxTickets: u32 = 5;
+	pub const MaxRafflesPerBlock: u32 = 1;
+}
+
+impl pallet_raffle::Config for Test {
+	type Event = Event;
+	type MinTickets = MinTickets;
+	type MaxTickets = MaxTickets;
+	type MaxRafflesPerBlock = MaxRafflesPerBlock;
+}
+
+pub fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		System::set_block_number(System::block_number() + 1);
+		RaffleModule::on_initialize(System::block_number());
+	}
+}
@@ -0,0 +1,104 @@
+use crate::{Error, Event, mock::*};
+use frame_support::{assert_ok, assert_noop};
+use super::*;
+
+#[test]
+fn start_raffle_works() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1]);
+		assert_ok!(RaffleModule::start_raffle(Origin::signed(1), (0, 0), 100, 3, 10));
+		System::assert_has_event(mock::Event::RaffleModule(Event::RaffleStart(1, (0, 0), 100, 3, 10)));
+		assert_eq!(RaffleModule::raffles_ending(10), vec![(0, 0)]);
+		assert!(KittyModule::is_locked((0, 0)));
+	})
+}
+
+#[test]
+fn start_raffle_failed() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1]);
+		assert_noop!(RaffleModule::start_raffle(Origin::signed(2), (0, 0), 100, 3, 10), Error::<Test>::NotOwner);
+		assert_noop!(RaffleModule::start_raffle(Origin::signed(1), (0, 0), 100, 3, 1), Error::<Test>::InvalidEndBlock);
+		assert_noop!(RaffleModule::start_raffle(Origin::signed(1), (0, 0), 100, 1, 10), Error::<Test>::InvalidMaxTickets);
+		assert_noop!(RaffleModule::start_raffle(Origin::signed(1), (0, 0), 100, 6, 10), Error::<Test>::InvalidMaxTickets);
+		assert_noop!(RaffleModule::start_raffle(Origin::signed(1), (0, 0), 0, 3, 10), Error::<Test>::InvalidTicketPrice);
+		assert_ok!(RaffleModule::start_raffle(Origin::signed(1), (0, 0), 100, 3, 10));
+		assert_noop!(RaffleModule::start_raffle(Origin::signed(1), (0, 0), 100, 3, 10), Error::<Test>::RaffleExists);
+
+		//同一区块开奖的抽奖数量有上限
+		create_kitties(&[1]);
+		assert_noop!(RaffleModule::start_raffle(Origin::signed(1), (0, 1), 100, 3, 10), Error::<Test>::TooManyRaffles);
+		assert_ok!(RaffleModule::start_raffle(Origin::signed(1), (0, 1), 100, 3, 11));
+	})
+}
+
+#[test]
+fn buy_ticket_failed() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1]);
+		assert_noop!(RaffleModule::buy_ticket(Origin::signed(2), (0, 0)), Error::<Test>::InvalidRaffle);
+		assert_ok!(RaffleModule::start_raffle(Origin::signed(1), (0, 0), 100, 2, 10));
+		assert_noop!(RaffleModule::buy_ticket(Origin::signed(1), (0, 0)), Error::<Test>::FromSameTo);
+		assert_noop!(RaffleModule::buy_ticket(Origin::signed(3), (0, 0)), Error::<Test>::BalanceLitter);
+		assert_ok!(RaffleModule::buy_ticket(Origin::signed(2), (0, 0)));
+		assert_ok!(RaffleModule::buy_ticket(Origin::signed(0), (0, 0)));
+		assert_noop!(RaffleModule::buy_ticket(Origin::signed(2), (0, 0)), Error::<Test>::SoldOut);
+	})
+}
+
+#[test]
+fn raffle_draws_winner() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1]);
+		assert_ok!(KittyModule::sell_kitty(Origin::signed(1), (0, 0), Some(500)));
+		assert_ok!(RaffleModule::start_raffle(Origin::signed(1), (0, 0), 100, 3, 10));
+		assert_ok!(RaffleModule::buy_ticket(Origin::signed(2), (0, 0)));
+		assert_ok!(RaffleModule::buy_ticket(Origin::signed(0), (0, 0)));
+		assert_ok!(RaffleModule::buy_ticket(Origin::signed(2), (0, 0)));
+		assert_eq!(Balances::reserved_balance(2), 200);
+
+		run_to_block(9);
+		assert_noop!(RaffleModule::buy_ticket(Origin::signed(0), (0, 0)), Error::<Test>::SoldOut);
+		run_to_block(10);
+
+		let winner = KittyModule::owner((0, 0)).unwrap();
+		assert!(winner == 0 || winner == 2);
+		System::assert_has_event(mock::Event::RaffleModule(Event::RaffleWin(winner, (0, 0), 300)));
+		assert_eq!(Balances::free_balance(1), 100_000_000 - 1_000 + 300);
+		assert_eq!(Balances::reserved_balance(0), 0);
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert!(RaffleModule::raffles((0, 0)).is_none());
+		//中奖后kitty解锁，原来的挂单失效
+		assert!(!KittyModule::is_locked((0, 0)));
+		assert_eq!(KittyModule::kitties_price((0, 0)), None);
+	})
+}
+
+#[test]
+fn raffle_refunds_when_too_few_tickets() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1]);
+		assert_ok!(RaffleModule::start_raffle(Origin::signed(1), (0, 0), 100, 3, 10));
+		assert_ok!(RaffleModule::buy_ticket(Origin::signed(2), (0, 0)));
+
+		run_to_block(10);
+		System::assert_has_event(mock::Event::RaffleModule(Event::RaffleRefund((0, 0))));
+		assert_eq!(Balances::reserved_balance(2), 0);
+		assert_eq!(KittyModule::owner((0, 0)), Some(1));
+		assert!(!KittyModule::is_locked((0, 0)));
+	})
+}
+
+#[test]
+fn raffle_locks_kitty() {
+	new_test_ext().execute_with(|| {
+		create_kitties(&[1]);
+		assert_ok!(KittyModule::sell_kitty(Origin::signed(1), (0, 0), Some(500)));
+		assert_ok!(RaffleModule::start_raffle(Origin::signed(1), (0, 0), 100, 3, 10));
+
+		//开奖前kitty不能转让、买卖或销毁
+		assert_noop!(KittyModule::transfer(Origin::signed(1), 0, (0, 0)), pallet_kitties::Error::<Test>::KittyLocked);
+		assert_noop!(KittyModule::buy_kitty(Origin::signed(2), (0, 0)), pallet_kitties::Error::<Test>::KittyLocked);
+		assert_noop!(KittyModule::burn(Origin::signed(1), (0, 0)), pallet_kitties::Error::<Test>::KittyLocked);
+	})
+}
@@ -97,10 +97,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 101,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 2,
 };
 
 /// This determines the average expected block time that we are targeting.
@@ -256,12 +256,37 @@ parameter_types! {
 }
 
 impl pallet_transaction_payment::Config for Runtime {
+	// Fees are burned; `KittyHolderDiscount` relies on this when it mints refunds.
 	type OnChargeTransaction = CurrencyAdapter<Balances, ()>;
 	type TransactionByteFee = TransactionByteFee;
 	type WeightToFee = IdentityFee<Balance>;
 	type FeeMultiplierUpdate = ();
 }
 
+parameter_types! {
+	pub const AssetDeposit: Balance = 100_000;
+	pub const ApprovalDeposit: Balance = 1_000;
+	pub const StringLimit: u32 = 50;
+	pub const MetadataDepositBase: Balance = 10_000;
+	pub const MetadataDepositPerByte: Balance = 100;
+}
+
+impl pallet_assets::Config for Runtime {
+	type Event = Event;
+	type Balance = Balance;
+	type AssetId = u32;
+	type Currency = Balances;
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+	type AssetDeposit = AssetDeposit;
+	type MetadataDepositBase = MetadataDepositBase;
+	type MetadataDepositPerByte = MetadataDepositPerByte;
+	type ApprovalDeposit = ApprovalDeposit;
+	type StringLimit = StringLimit;
+	type Freezer = ();
+	type Extra = ();
+	type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
+}
+
 impl pallet_sudo::Config for Runtime {
 	type Event = Event;
 	type Call = Call;
@@ -270,6 +295,15 @@ impl pallet_sudo::Config for Runtime {
 parameter_types! {
 	pub const ProofLenght: u64 = 5;
 	pub const KittyDepositBase: u32 = 1_000;
+	pub const CollectionDeposit: Balance = 100_000;
+	pub const MaxMetadataLength: u32 = 128;
+	pub const MaxHistoryLength: u32 = 20;
+	pub const BreedRevealDelay: BlockNumber = 3;
+	pub const BreedRevealWindow: BlockNumber = 10 * MINUTES;
+	pub const BreedDeposit: Balance = 500;
+	pub const MaxBreedsPerBlock: u32 = 50;
+	pub const StarterClaimsPerBlock: u32 = 10;
+	pub const MaxStarterKitties: u32 = 10_000;
 }
 /// Configure the pallet-template in pallets/template.
 impl pallet_poe::Config for Runtime {
@@ -283,6 +317,74 @@ impl pallet_kitties::Config for Runtime {
 	type KittyIndex = Index;
 	type Currency = Balances;
 	type KittyDepositBase = KittyDepositBase;
+	type CollectionId = u32;
+	type CollectionDeposit = CollectionDeposit;
+	type MaxMetadataLength = MaxMetadataLength;
+	type OffchainSignature = Signature;
+	type OffchainPublic = <Signature as Verify>::Signer;
+	type Assets = Assets;
+	type OnKittyCreated = KittyDiscounts;
+	type OnKittyTransferred = KittyDiscounts;
+	type OnKittyBurned = KittyDiscounts;
+	type MaxHistoryLength = MaxHistoryLength;
+	type BreedRevealDelay = BreedRevealDelay;
+	type BreedRevealWindow = BreedRevealWindow;
+	type BreedDeposit = BreedDeposit;
+	type MaxBreedsPerBlock = MaxBreedsPerBlock;
+	type StarterClaimsPerBlock = StarterClaimsPerBlock;
+	type MaxStarterKitties = MaxStarterKitties;
+}
+
+parameter_types! {
+	pub const RoundDuration: BlockNumber = 10 * MINUTES;
+	pub const MaxEntrants: u32 = 64;
+	pub const BattleDelay: BlockNumber = 3;
+	pub const MaxBattlesPerBlock: u32 = 32;
+}
+
+impl pallet_battles::Config for Runtime {
+	type Event = Event;
+	type TournamentOrigin = frame_system::EnsureRoot<AccountId>;
+	type RoundDuration = RoundDuration;
+	type MaxEntrants = MaxEntrants;
+	type BattleDelay = BattleDelay;
+	type MaxBattlesPerBlock = MaxBattlesPerBlock;
+}
+
+parameter_types! {
+	pub const MinTickets: u32 = 2;
+	pub const MaxTickets: u32 = 1_000;
+	pub const MaxRafflesPerBlock: u32 = 32;
+}
+
+impl pallet_raffle::Config for Runtime {
+	type Event = Event;
+	type MinTickets = MinTickets;
+	type MaxTickets = MaxTickets;
+	type MaxRafflesPerBlock = MaxRafflesPerBlock;
+}
+
+parameter_types! {
+	pub const MaxLoanDuration: BlockNumber = 30 * DAYS;
+}
+
+impl pallet_loans::Config for Runtime {
+	type Event = Event;
+	type MaxLoanDuration = MaxLoanDuration;
+}
+
+impl pallet_fractional::Config for Runtime {
+	type Event = Event;
+}
+
+parameter_types! {
+	pub const MaxTiers: u32 = 10;
+}
+
+impl pallet_discounts::Config for Runtime {
+	type Event = Event;
+	type DiscountOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxTiers = MaxTiers;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -302,7 +404,13 @@ construct_runtime!(
 		Sudo: pallet_sudo::{Pallet, Call, Config<T>, Storage, Event<T>},
 		// Include the custom logic from the pallet-template in the runtime.
 		TemplateModule: pallet_poe::{Pallet, Call, Storage, Event<T>},
-		KittiesModule: pallet_kitties::{Pallet, Call, Storage, Event<T>},
+		KittiesModule: pallet_kitties::{Pallet, Call, Storage, Event<T>, Config<T>, ValidateUnsigned},
+		KittyBattles: pallet_battles::{Pallet, Call, Storage, Event<T>},
+		KittyRaffle: pallet_raffle::{Pallet, Call, Storage, Event<T>},
+		KittyLoans: pallet_loans::{Pallet, Call, Storage, Event<T>},
+		KittyVaults: pallet_fractional::{Pallet, Call, Storage, Event<T>},
+		KittyDiscounts: pallet_discounts::{Pallet, Call, Storage, Event<T>},
+		Assets: pallet_assets::{Pallet, Call, Storage, Event<T>},
 	}
 );
 
@@ -320,7 +428,8 @@ pub type SignedExtra = (
 	frame_system::CheckEra<Runtime>,
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
-	pallet_transaction_payment::ChargeTransactionPayment<Runtime>
+	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	pallet_discounts::KittyHolderDiscount<Runtime>,
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
@@ -332,6 +441,13 @@ pub type Executive = frame_executive::Executive<
 	Runtime,
 	AllPallets,
 >;
+/// Identifies a kitty by its collection and its index within that collection.
+pub type KittyId = pallet_kitties::KittyIdOf<Runtime>;
+/// A kitty as returned by the kitties runtime API.
+pub type KittyInfo = pallet_kitties::KittyInfoOf<Runtime>;
+/// One entry in a kitty's provenance.
+pub type KittyHistoryEntry = pallet_kitties::HistoryEntryOf<Runtime>;
+pub use pallet_kitties::Phenotype;
 
 impl_runtime_apis! {
 	impl sp_api::Core<Block> for Runtime {
@@ -459,6 +575,35 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_kitties_rpc_runtime_api::KittiesApi<
+		Block,
+		KittyId,
+		AccountId,
+		KittyInfo,
+		Phenotype,
+		KittyHistoryEntry,
+	> for Runtime {
+		fn kitty(kitty_id: KittyId) -> Option<KittyInfo> {
+			KittiesModule::kitties(kitty_id)
+		}
+
+		fn kitties_of(owner: AccountId, start_after: Option<KittyId>, limit: u32) -> Vec<(KittyId, KittyInfo)> {
+			KittiesModule::kitties_of(&owner, start_after, limit)
+		}
+
+		fn listings(start_after: Option<KittyId>, limit: u32) -> Vec<(KittyId, KittyInfo)> {
+			KittiesModule::listings(start_after, limit)
+		}
+
+		fn phenotype(kitty_id: KittyId) -> Option<Phenotype> {
+			KittiesModule::phenotype(kitty_id)
+		}
+
+		fn kitty_history(kitty_id: KittyId) -> Vec<KittyHistoryEntry> {
+			KittiesModule::history_of(kitty_id)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn dispatch_benchmark(